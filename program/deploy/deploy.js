@@ -11,6 +11,8 @@ import {
   PublicKey,
   LAMPORTS_PER_SOL,
   SystemProgram,
+  SYSVAR_CLOCK_PUBKEY,
+  SYSVAR_RENT_PUBKEY,
   TransactionInstruction,
   Transaction,
 } from '@solana/web3.js';
@@ -22,6 +24,30 @@ let TOKEN_PROGRAM_ID = new PublicKey(
 
 let TREASURY_TOKEN_PRECISION = 9;
 
+// Auction parameters saved into the AuctionConfig on InitiateAuction
+let MAX_BID_COUNT = 1000;
+let BOARD_SIDE = 8;
+let TEAM_COUNT = 4;
+let STARTING_HEALTH = 100000000;
+let SHOWDOWN_DELAY_SLOTS = 6000000;
+let SOFT_CLOSE_SLOTS = 150;
+let EXTENSION_SLOTS = 75;
+let MAX_EXTENSION_SLOTS = 9000;
+let MIN_BID_LAMPORTS = 10000000;
+let RESERVE_PRICE_LAMPORTS = 1000000000;
+let RESERVE_STEP_LAMPORTS = 10000000;
+let MIN_INCREMENT_LAMPORTS = 10000000;
+let TOP_UP_KEEPS_PRIORITY = false;
+// 0 takes open bids, otherwise bids are sealed and revealed for this many slots after the auction
+let REVEAL_SLOTS = 0;
+let FORFEIT_UNREVEALED = false;
+// 0 English auction, 1 Dutch auction selling squares with BuySquare at a price dropping every slot
+let AUCTION_MODE = 0;
+let DUTCH_START_PRICE_LAMPORTS = 5000000000;
+let DUTCH_PRICE_STEP_LAMPORTS = 100000;
+// Mint bids and the prize are paid in (ie: USDC), empty keeps bids in native SOL
+let BID_MINT = '';
+
 import fs from 'mz/fs';
 import * as BufferLayout from 'buffer-layout';
 
@@ -88,18 +114,15 @@ async function loadStore() {
     let payerAccount;
     let programId;
     let auctionInfoPubkey;
-    let auctionListPubkey;
     let treasuryPubkey;
     let allGameSquaresListPubkey;
     let activePlayersListPubkey;
+    let bidIndexPubkey;
     try {
       let config = await store.load('config.json');
       if (config.programId !== "") {
           programId = new PublicKey(config.programId);
       }
-      if (config.auctionListPubkey !== "") {
-          auctionListPubkey = new PublicKey(config.auctionListPubkey);
-      }
       if (config.auctionInfoPubkey !== "") {
           auctionInfoPubkey = new PublicKey(config.auctionInfoPubkey);
       }
@@ -109,6 +132,9 @@ async function loadStore() {
       if (config.activePlayersListPubkey !== "") {
           activePlayersListPubkey = new PublicKey(config.activePlayersListPubkey);
       }
+      if (config.bidIndexPubkey !== "") {
+          bidIndexPubkey = new PublicKey(config.bidIndexPubkey);
+      }
       if (config.treasuryPubkey !== "") {
           treasuryPubkey = new PublicKey(config.treasuryPubkey);
       }
@@ -119,18 +145,18 @@ async function loadStore() {
     } catch (err) {
       console.log(err);
     }
-    return [store, programId, payerAccount, auctionListPubkey, treasuryPubkey, auctionInfoPubkey, allGameSquaresListPubkey, activePlayersListPubkey];
+    return [store, programId, payerAccount, treasuryPubkey, auctionInfoPubkey, allGameSquaresListPubkey, activePlayersListPubkey, bidIndexPubkey];
 }
 
-async function saveStore(store, urlTls, programId, payerSecretKey, auctionListPubkey, treasuryPubkey, auctionInfoPubkey, allGameSquaresListPubkey, activePlayersListPubkey) {
+async function saveStore(store, urlTls, programId, payerSecretKey, treasuryPubkey, auctionInfoPubkey, allGameSquaresListPubkey, activePlayersListPubkey, bidIndexPubkey) {
     try {
       await store.save('config.json', {
         url: urlTls,
         programId: typeof programId !== 'undefined' ? programId.toBase58() : '',
-        auctionListPubkey: typeof auctionListPubkey !== 'undefined' ? auctionListPubkey.toBase58() : '',
         auctionInfoPubkey: typeof auctionInfoPubkey !== 'undefined' ? auctionInfoPubkey.toBase58() : '',
         allGameSquaresListPubkey: typeof allGameSquaresListPubkey !== 'undefined' ? allGameSquaresListPubkey.toBase58() : '',
         activePlayersListPubkey: typeof activePlayersListPubkey !== 'undefined' ? activePlayersListPubkey.toBase58() : '',
+        bidIndexPubkey: typeof bidIndexPubkey !== 'undefined' ? bidIndexPubkey.toBase58() : '',
         treasuryPubkey: typeof treasuryPubkey !== 'undefined' ? treasuryPubkey.toBase58() : '',
         payerSecretKey: typeof payerSecretKey !== 'undefined' ? payerSecretKey : '',
       });
@@ -148,11 +174,11 @@ export async function establishOwner(): Promise<void> {
       store,
       programId,
       payerAccount,
-      auctionListPubkey,
       treasuryPubkey,
       auctionInfoPubkey,
       allGameSquaresListPubkey,
-      activePlayersListPubkey
+      activePlayersListPubkey,
+      bidIndexPubkey
   ] = await loadStore();
   if (!payerAccount) {
     let fees = 0;
@@ -186,11 +212,11 @@ export async function establishOwner(): Promise<void> {
     urlTls,
     programId,
     payerSecretKey,
-    auctionListPubkey,
     treasuryPubkey,
     auctionInfoPubkey,
     allGameSquaresListPubkey,
-    activePlayersListPubkey
+    activePlayersListPubkey,
+    bidIndexPubkey
   );
 }
 
@@ -203,13 +229,14 @@ export async function loadProgram(): Promise<void> {
     store,
     programId,
     payerAccount,
-    auctionListPubkey,
     treasuryPubkey,
     auctionInfoPubkey,
     allGameSquaresListPubkey,
-    activePlayersListPubkey
+    activePlayersListPubkey,
+    bidIndexPubkey
   ] = await loadStore();
   let loaded = false;
+  let programAccount;
   if (typeof programId !== 'undefined') {
     await connection.getAccountInfo(programId);
     console.log('Program already loaded to account ' + programId.toBase58());
@@ -220,7 +247,7 @@ export async function loadProgram(): Promise<void> {
       // Load the program
       console.log('Loading sixtyfourgame program...');
       const data = await fs.readFile(pathToProgram);
-      let programAccount = new Account();
+      programAccount = new Account();
       console.log(payerAccount.publicKey.toBase58());
       console.log(programAccount.publicKey.toBase58());
 
@@ -241,29 +268,28 @@ export async function loadProgram(): Promise<void> {
 
   // TODO: CLEAN UP THIS REPEATED CODE
 
-  if (typeof auctionListPubkey == "undefined" || auctionListPubkey == "") {
-      // Create the auctionList account
-      const auctionListAccount = new Account();
-      auctionListPubkey = auctionListAccount.publicKey;
-      console.log('Creating Auction List with address ', auctionListPubkey.toBase58(), ' for the game');
+  if (typeof bidIndexPubkey == "undefined" || bidIndexPubkey == "") {
+      // Create the bidIndex account
+      const bidIndexAccount = new Account();
+      bidIndexPubkey = bidIndexAccount.publicKey;
+      console.log('Creating Bid Index with address ', bidIndexPubkey.toBase58(), ' for the game');
 
-      let auctionListAccountSecretKey = Buffer.from(auctionListAccount.secretKey).toString("base64");
-      console.log('auctionListAccountSecretKey   ', auctionListAccountSecretKey);
+      let bidIndexAccountSecretKey = Buffer.from(bidIndexAccount.secretKey).toString("base64");
+      console.log('bidIndexAccountSecretKey   ', bidIndexAccountSecretKey);
 
-      // Account needs data for 64 pubkeys, u64, u64
-      let numBids = 1000;
-      let space = numBids * 48;
-      console.log('Auction List using ', space.toString(), ' allocated bytes');
+      // Account needs a u64 count and a u64 amount, u64 priority, u64 bid number per bid
+      let space = 8 + MAX_BID_COUNT * 24;
+      console.log('Bid Index using ', space.toString(), ' allocated bytes');
 
       // Get rent exempt amount of lamports
       const lamports =  await connection.getMinimumBalanceForRentExemption(space);
-      console.log('Rent-exempt lamports for auction list: ',lamports.toString());
+      console.log('Rent-exempt lamports for bid index: ',lamports.toString());
 
-      // Create Auction List
+      // Create Bid Index
       const transaction = new Transaction().add(
         SystemProgram.createAccount({
           fromPubkey: payerAccount.publicKey,
-          newAccountPubkey: auctionListPubkey,
+          newAccountPubkey: bidIndexPubkey,
           lamports,
           space,
           programId,
@@ -274,10 +300,10 @@ export async function loadProgram(): Promise<void> {
         connection,
         transaction,
         payerAccount,
-        auctionListAccount,
+        bidIndexAccount,
       );
   }
-  console.log("Auction List address: " + auctionListPubkey.toBase58());
+  console.log("Bid Index address: " + bidIndexPubkey.toBase58());
 
   if (typeof allGameSquaresListPubkey == "undefined" || allGameSquaresListPubkey == "") {
       // Create the allGameSquaresList account
@@ -288,9 +314,9 @@ export async function loadProgram(): Promise<void> {
       let allGameSquaresListAccountSecretKey = Buffer.from(allGameSquaresListAccount.secretKey).toString("base64");
       console.log('allGameSquaresListAccountSecretKey   ', allGameSquaresListAccountSecretKey);
 
-      // Account needs data for 64 GameSquares
-      let gameSquareCount = 64;
-      let space = gameSquareCount * 56;
+      // Account needs data for every GameSquare on the board
+      let gameSquareCount = BOARD_SIDE * BOARD_SIDE;
+      let space = gameSquareCount * 58;
       console.log('allGameSquaresList using ', space.toString(), ' allocated bytes');
 
       // Get rent exempt amount of lamports
@@ -327,9 +353,9 @@ export async function loadProgram(): Promise<void> {
       let activePlayersListAccountSecretKey = Buffer.from(activePlayersListAccount.secretKey).toString("base64");
       console.log('activePlayersListAccountSecretKey   ', activePlayersListAccountSecretKey);
 
-      // Account needs data for 64 activePlayers
-      let activePlayerCount = 64;
-      let space = activePlayerCount * 72;
+      // Account needs data for an activePlayer per square on the board
+      let activePlayerCount = BOARD_SIDE * BOARD_SIDE;
+      let space = activePlayerCount * 130;
       console.log('activePlayersList using ', space.toString(), ' allocated bytes');
 
       // Get rent exempt amount of lamports
@@ -356,41 +382,11 @@ export async function loadProgram(): Promise<void> {
   }
   console.log("activePlayersList address: " + activePlayersListPubkey.toBase58());
 
-  if (typeof treasuryPubkey == "undefined" || treasuryPubkey == "") {
-      // Create the auctionList account
-      const treasuryAccount = new Account();
-      treasuryPubkey = treasuryAccount.publicKey;
-      console.log('Creating Treasury with address ', treasuryPubkey.toBase58(), ' for the game');
-
-      let treasuryAccountSecretKey = Buffer.from(treasuryAccount.secretKey).toString("base64");
-      console.log('treasuryAccountSecretKey   ', treasuryAccountSecretKey);
-
-      // Account needs no data
-      let space = 0;
-      console.log('Treasury using ', space.toString(), ' allocated bytes');
-
-      // Get rent exempt amount of lamports
-      const lamports =  await connection.getMinimumBalanceForRentExemption(space);
-      console.log('Rent-exempt lamports for Treasury: ',lamports.toString());
-
-      // Create Auction List
-      const transaction = new Transaction().add(
-        SystemProgram.createAccount({
-          fromPubkey: payerAccount.publicKey,
-          newAccountPubkey: treasuryPubkey,
-          lamports,
-          space,
-          programId,
-        }),
-      );
-      await sendAndConfirmTransaction(
-        'createAccount',
-        connection,
-        transaction,
-        payerAccount,
-        treasuryAccount,
-      );
-  }
+  // Treasury is a program address, created by InitiateAuction
+  [treasuryPubkey] = await PublicKey.findProgramAddress(
+      [Buffer.from("treasury")],
+      programId,
+  );
   console.log("Treasury address: " + treasuryPubkey.toBase58());
 
   if (typeof auctionInfoPubkey == "undefined" || auctionInfoPubkey == "") {
@@ -402,8 +398,8 @@ export async function loadProgram(): Promise<void> {
       let auctionInfoAccountSecretKey = Buffer.from(auctionInfoAccount.secretKey).toString("base64");
       console.log('auctionInfoAccountSecretKey   ', auctionInfoAccountSecretKey);
 
-      // Account needs 25 bytes for auction info
-      let space = 25;
+      // Account needs 32 bytes for auction info
+      let space = 56;
       console.log('auctionInfo using ', space.toString(), ' allocated bytes');
 
       // Get rent exempt amount of lamports
@@ -440,13 +436,83 @@ export async function loadProgram(): Promise<void> {
   console.log("Current block " + lastSlot);
   console.log("Auction end slot: " + auctionEndSlot);
 
+  const [auctionConfigPubkey] = await PublicKey.findProgramAddress(
+      [Buffer.from("config")],
+      programId,
+  );
+  console.log("auctionConfig address: " + auctionConfigPubkey.toBase58());
+
+  const [gameStatePubkey] = await PublicKey.findProgramAddress(
+      [Buffer.from("game_state")],
+      programId,
+  );
+  console.log("gameState address: " + gameStatePubkey.toBase58());
+
+  // Token bids are escrowed in a program token account created by InitiateAuction
+  let bidMintKeys = [];
+  if (BID_MINT) {
+      const [bidEscrowPubkey] = await PublicKey.findProgramAddress(
+          [Buffer.from("bid_escrow")],
+          programId,
+      );
+      const [programTokenOwnerPubkey] = await PublicKey.findProgramAddress(
+          [Buffer.from("initiate")],
+          programId,
+      );
+      console.log("bidEscrow address: " + bidEscrowPubkey.toBase58());
+      bidMintKeys = [
+          {pubkey: new PublicKey(BID_MINT), isSigner: false, isWritable: false},
+          {pubkey: bidEscrowPubkey, isSigner: false, isWritable: true},
+          {pubkey: programTokenOwnerPubkey, isSigner: false, isWritable: false},
+          {pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false},
+      ];
+  }
+
   const instruction = new TransactionInstruction({
       keys: [{pubkey: payerAccount.publicKey, isSigner: true, isWritable: true},
-          {pubkey: auctionInfoPubkey, isSigner: false, isWritable: true}],
+          {pubkey: auctionInfoPubkey, isSigner: false, isWritable: true},
+          {pubkey: auctionConfigPubkey, isSigner: false, isWritable: true},
+          {pubkey: treasuryPubkey, isSigner: false, isWritable: true},
+          {pubkey: gameStatePubkey, isSigner: false, isWritable: true},
+          {pubkey: SYSVAR_RENT_PUBKEY, isSigner: false, isWritable: false},
+          {pubkey: SystemProgram.programId, isSigner: false, isWritable: false},
+          {pubkey: bidIndexPubkey, isSigner: false, isWritable: true},
+          {pubkey: SYSVAR_CLOCK_PUBKEY, isSigner: false, isWritable: false},
+          {pubkey: programId, isSigner: true, isWritable: false},
+          {pubkey: allGameSquaresListPubkey, isSigner: false, isWritable: false},
+          {pubkey: activePlayersListPubkey, isSigner: false, isWritable: false},
+          ...bidMintKeys],
       programId,
-      data: Buffer.from([0, ...longToByteArray(auctionEndSlot)])
+      data: Buffer.from([
+          0,
+          ...longToByteArray(auctionEndSlot),
+          ...longToByteArray(MAX_BID_COUNT),
+          ...longToByteArray(BOARD_SIDE),
+          ...longToByteArray(TEAM_COUNT),
+          ...longToByteArray(STARTING_HEALTH),
+          ...longToByteArray(SHOWDOWN_DELAY_SLOTS),
+          ...longToByteArray(SOFT_CLOSE_SLOTS),
+          ...longToByteArray(EXTENSION_SLOTS),
+          ...longToByteArray(MAX_EXTENSION_SLOTS),
+          ...longToByteArray(MIN_BID_LAMPORTS),
+          ...longToByteArray(RESERVE_PRICE_LAMPORTS),
+          ...longToByteArray(RESERVE_STEP_LAMPORTS),
+          ...longToByteArray(MIN_INCREMENT_LAMPORTS),
+          TOP_UP_KEEPS_PRIORITY ? 1 : 0,
+          ...longToByteArray(REVEAL_SLOTS),
+          FORFEIT_UNREVEALED ? 1 : 0,
+          AUCTION_MODE,
+          ...longToByteArray(DUTCH_START_PRICE_LAMPORTS),
+          ...longToByteArray(DUTCH_PRICE_STEP_LAMPORTS),
+          ...(BID_MINT ? new PublicKey(BID_MINT).toBuffer() : Buffer.alloc(32)),
+      ])
   });
 
+  // The program account co-signs, only a freshly loaded program can be initiated
+  if (!programAccount) {
+    console.log('Program keypair unavailable, cannot initiate the auction');
+    return;
+  }
   console.log('Setting auction end slot');
   let instructions = new Transaction();
   instructions.add(instruction);
@@ -454,7 +520,8 @@ export async function loadProgram(): Promise<void> {
     '',
     connection,
     instructions,
-    payerAccount
+    payerAccount,
+    programAccount
   );
 
   await saveStore(
@@ -462,11 +529,11 @@ export async function loadProgram(): Promise<void> {
     urlTls,
     programId,
     payerSecretKey,
-    auctionListPubkey,
     treasuryPubkey,
     auctionInfoPubkey,
     allGameSquaresListPubkey,
-    activePlayersListPubkey
+    activePlayersListPubkey,
+    bidIndexPubkey
   );
 }
 
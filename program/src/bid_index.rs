@@ -0,0 +1,195 @@
+//! Sorted index of the active bids
+//!
+//! The bid index account starts with the number of indexed bids as a little
+//! endian u64, followed by one (amount_lamports, priority, bid_number) entry per
+//! active bid, highest amount first. Bid inserts and CancelBid removes with a
+//! binary search, so once the auction ends the winner of square N is entry N.
+
+use crate::error::SixtyFourGameError;
+use solana_sdk::program_error::ProgramError;
+use std::{cmp::Ordering, convert::TryInto};
+
+/// Bytes of the indexed bid count
+pub const BID_INDEX_HEADER_LEN: usize = 8;
+
+/// Bytes of an (amount_lamports, priority, bid_number) entry
+pub const BID_INDEX_ENTRY_LEN: usize = 24;
+
+/// Account size needed to index max_bid_count bids
+pub fn bid_index_space(max_bid_count: u64) -> usize {
+    BID_INDEX_HEADER_LEN + max_bid_count as usize * BID_INDEX_ENTRY_LEN
+}
+
+/// Number of indexed bids
+pub fn len(data: &[u8]) -> u64 {
+    u64::from_le_bytes(data[..BID_INDEX_HEADER_LEN].try_into().unwrap())
+}
+
+fn set_len(data: &mut [u8], len: u64) {
+    data[..BID_INDEX_HEADER_LEN].copy_from_slice(&len.to_le_bytes());
+}
+
+fn entry_offset(rank: u64) -> usize {
+    BID_INDEX_HEADER_LEN + rank as usize * BID_INDEX_ENTRY_LEN
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+/// Empties the index
+pub fn clear(data: &mut [u8]) {
+    set_len(data, 0);
+}
+
+/// Returns the (amount_lamports, priority, bid_number) entry ranked `rank`, 0 is the highest bid
+pub fn get(data: &[u8], rank: u64) -> Option<(u64, u64, u64)> {
+    if rank >= len(data) {
+        return None;
+    }
+    let offset = entry_offset(rank);
+    Some((read_u64(data, offset), read_u64(data, offset + 8), read_u64(data, offset + 16)))
+}
+
+/// Auction ranking of two (amount_lamports, priority) bids, Less if a ranks ahead of b.
+/// Higher amounts rank first, a tie goes to the lower priority, the bid placed first
+pub fn compare_bids(a: (u64, u64), b: (u64, u64)) -> Ordering {
+    b.0.cmp(&a.0).then(a.1.cmp(&b.1))
+}
+
+/// Rank the bid has or would get once inserted, the number of indexed bids ranked ahead of it
+pub fn rank(data: &[u8], amount: u64, priority: u64) -> u64 {
+    let (mut low, mut high) = (0, len(data));
+    while low < high {
+        let mid = low + (high - low) / 2;
+        let (mid_amount, mid_priority, _) = get(data, mid).unwrap();
+        if compare_bids((mid_amount, mid_priority), (amount, priority)) == Ordering::Less {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    low
+}
+
+/// Adds a bid in rank order and returns its rank
+pub fn insert(data: &mut [u8], amount: u64, priority: u64, bid_number: u64) -> Result<u64, ProgramError> {
+    let count = len(data);
+    if entry_offset(count + 1) > data.len() {
+        return Err(SixtyFourGameError::AuctionFull.into());
+    }
+    let rank = rank(data, amount, priority);
+    let offset = entry_offset(rank);
+    data.copy_within(offset..entry_offset(count), offset + BID_INDEX_ENTRY_LEN);
+    data[offset..offset + 8].copy_from_slice(&amount.to_le_bytes());
+    data[offset + 8..offset + 16].copy_from_slice(&priority.to_le_bytes());
+    data[offset + 16..offset + 24].copy_from_slice(&bid_number.to_le_bytes());
+    set_len(data, count + 1);
+    Ok(rank)
+}
+
+/// Removes a bid, later bids move up one rank
+pub fn remove(data: &mut [u8], amount: u64, priority: u64, bid_number: u64) -> Result<(), ProgramError> {
+    let count = len(data);
+    let rank = rank(data, amount, priority);
+    if get(data, rank) != Some((amount, priority, bid_number)) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let offset = entry_offset(rank);
+    data.copy_within(offset + BID_INDEX_ENTRY_LEN..entry_offset(count), offset);
+    set_len(data, count - 1);
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // Plain bids keep the priority they were placed with, their bid number
+    fn entries(data: &[u8]) -> Vec<(u64, u64)> {
+        (0..len(data))
+            .map(|rank| {
+                let (amount, priority, bid_number) = get(data, rank).unwrap();
+                assert_eq!(priority, bid_number);
+                (amount, bid_number)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_compare_bids() {
+        assert_eq!(compare_bids((20, 5), (10, 1)), Ordering::Less);
+        assert_eq!(compare_bids((10, 1), (20, 5)), Ordering::Greater);
+        // Tie goes to the bid placed first
+        assert_eq!(compare_bids((10, 1), (10, 5)), Ordering::Less);
+        assert_eq!(compare_bids((10, 5), (10, 1)), Ordering::Greater);
+        assert_eq!(compare_bids((10, 5), (10, 5)), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_index_matches_sorted_bids() {
+        let bids = [(7, 0), (3, 1), (7, 2), (9, 3), (3, 4), (1, 5), (9, 6), (7, 7)];
+        let mut data = vec![0; bid_index_space(bids.len() as u64)];
+        for (amount, bid_number) in bids.iter() {
+            insert(&mut data, *amount, *bid_number, *bid_number).unwrap();
+        }
+        remove(&mut data, 7, 2, 2).unwrap();
+        let mut sorted: Vec<(u64, u64)> = bids.iter().cloned().filter(|bid| *bid != (7, 2)).collect();
+        sorted.sort_by(|a, b| compare_bids(*a, *b));
+        assert_eq!(entries(&data), sorted);
+        assert_eq!(entries(&data), vec![(9, 3), (9, 6), (7, 0), (7, 7), (3, 1), (3, 4), (1, 5)]);
+    }
+
+    #[test]
+    fn test_insert_keeps_rank_order() {
+        let mut data = vec![0; bid_index_space(6)];
+        assert_eq!(insert(&mut data, 10, 0, 0).unwrap(), 0);
+        assert_eq!(insert(&mut data, 30, 1, 1).unwrap(), 0);
+        assert_eq!(insert(&mut data, 20, 2, 2).unwrap(), 1);
+        assert_eq!(insert(&mut data, 30, 3, 3).unwrap(), 1);
+        assert_eq!(insert(&mut data, 5, 4, 4).unwrap(), 4);
+        assert_eq!(entries(&data), vec![(30, 1), (30, 3), (20, 2), (10, 0), (5, 4)]);
+        assert_eq!(get(&data, 5), None);
+    }
+
+    #[test]
+    fn test_insert_full() {
+        let mut data = vec![0; bid_index_space(2)];
+        insert(&mut data, 1, 0, 0).unwrap();
+        insert(&mut data, 2, 1, 1).unwrap();
+        assert!(insert(&mut data, 3, 2, 2).is_err());
+        assert_eq!(entries(&data), vec![(2, 1), (1, 0)]);
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut data = vec![0; bid_index_space(4)];
+        insert(&mut data, 10, 0, 0).unwrap();
+        insert(&mut data, 20, 1, 1).unwrap();
+        insert(&mut data, 10, 2, 2).unwrap();
+        insert(&mut data, 15, 3, 3).unwrap();
+        remove(&mut data, 10, 0, 0).unwrap();
+        assert_eq!(entries(&data), vec![(20, 1), (15, 3), (10, 2)]);
+        assert!(remove(&mut data, 10, 0, 0).is_err());
+        assert!(remove(&mut data, 10, 2, 0).is_err());
+        remove(&mut data, 20, 1, 1).unwrap();
+        remove(&mut data, 10, 2, 2).unwrap();
+        remove(&mut data, 15, 3, 3).unwrap();
+        assert_eq!(len(&data), 0);
+    }
+
+    #[test]
+    fn test_top_up_priority() {
+        let mut data = vec![0; bid_index_space(3)];
+        insert(&mut data, 10, 0, 0).unwrap();
+        insert(&mut data, 20, 1, 1).unwrap();
+        insert(&mut data, 15, 2, 2).unwrap();
+        // Bid 0 tops up to 20 keeping its priority, it moves ahead of bid 1
+        remove(&mut data, 10, 0, 0).unwrap();
+        assert_eq!(insert(&mut data, 20, 0, 0).unwrap(), 0);
+        // Bid 2 tops up to 20 with a new priority, it stays behind both
+        remove(&mut data, 15, 2, 2).unwrap();
+        assert_eq!(insert(&mut data, 20, 3, 2).unwrap(), 2);
+        assert_eq!(get(&data, 2), Some((20, 3, 2)));
+    }
+}
@@ -0,0 +1,211 @@
+//! Game board topology
+//!
+//! Squares are numbered by rank starting at 0 and laid out as a clockwise
+//! spiral from the middle of the board, moving east, south, west, north with
+//! arms of length 1, 1, 2, 2, 3, 3, ... (the README board image numbers the
+//! same squares from 1 to 64). Kept free of on-chain state so off-chain tools
+//! can share it.
+
+/// Board of the original season, 8 x 8 squares
+pub const DEFAULT_BOARD_SIDE: u64 = 8;
+
+/// Largest board side the program accepts, bounds compute spent walking the spiral
+pub const MAX_BOARD_SIDE: u64 = 16;
+
+/// Roll-under number of an even fight
+pub const BASE_ROLL_UNDER: u64 = 51;
+
+/// Extra roll-under percent the top ranked square gets over the last one
+pub const MAX_ADVANTAGE_PERCENT: u64 = 4;
+
+/// Row/column offsets of the north, east, south and west neighbors, row 0 is the north edge
+const DIRECTIONS: [(i64, i64); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+
+/// Walks the board positions in rank order
+struct Spiral {
+    row: i64,
+    col: i64,
+    direction: usize,
+    arm_length: u64,
+    arm_step: u64,
+    remaining: u64,
+}
+
+impl Spiral {
+    fn new(side: u64) -> Self {
+        let start = ((side.max(1) - 1) / 2) as i64;
+        Spiral {
+            row: start,
+            col: start,
+            direction: 1,
+            arm_length: 1,
+            arm_step: 0,
+            remaining: side * side,
+        }
+    }
+}
+
+impl Iterator for Spiral {
+    type Item = (u64, u64);
+
+    fn next(&mut self) -> Option<(u64, u64)> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let current = (self.row as u64, self.col as u64);
+        self.remaining -= 1;
+
+        let (d_row, d_col) = DIRECTIONS[self.direction];
+        self.row += d_row;
+        self.col += d_col;
+        self.arm_step += 1;
+        if self.arm_step == self.arm_length {
+            // Turn clockwise, arms grow after every second turn
+            self.arm_step = 0;
+            self.direction = (self.direction + 1) % 4;
+            if self.direction % 2 == 1 {
+                self.arm_length += 1;
+            }
+        }
+        Some(current)
+    }
+}
+
+/// Row and column of a square, None if the square is not on the board
+pub fn position(side: u64, square: u64) -> Option<(u64, u64)> {
+    Spiral::new(side).nth(square as usize)
+}
+
+/// Square at a row and column, None if off the board
+pub fn square_at(side: u64, row: u64, col: u64) -> Option<u64> {
+    Spiral::new(side)
+        .position(|p| p == (row, col))
+        .map(|square| square as u64)
+}
+
+/// North, east, south and west neighbors of a square that are on the board
+pub fn neighbors(side: u64, square: u64) -> impl Iterator<Item = u64> {
+    let position = position(side, square);
+    DIRECTIONS.iter().filter_map(move |(d_row, d_col)| {
+        let (row, col) = position?;
+        let row = row as i64 + d_row;
+        let col = col as i64 + d_col;
+        if row < 0 || col < 0 || row >= side as i64 || col >= side as i64 {
+            return None;
+        }
+        square_at(side, row as u64, col as u64)
+    })
+}
+
+/// True if the squares share an edge
+pub fn is_neighbor(side: u64, square: u64, other_square: u64) -> bool {
+    neighbors(side, square).any(|neighbor| neighbor == other_square)
+}
+
+/// Number an attack roll from 1 to 100 must stay under for the attacker to win,
+/// advantage shrinks linearly from MAX_ADVANTAGE_PERCENT for rank 0 to none for the last rank
+pub fn roll_under(square_count: u64, square: u64) -> u64 {
+    if square_count == 0 || square >= square_count {
+        return BASE_ROLL_UNDER;
+    }
+    BASE_ROLL_UNDER + (square_count - square) * MAX_ADVANTAGE_PERCENT / square_count
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // Neighbor table the Attack instruction used to hardcode, 1-based with 0 for no neighbor
+    const LEGACY_ATTACKABLE: [[u64; 4]; 64] = [
+        [2,4,6,8], [1,3,9,11], [2,4,12,14], [1,3,5,15], [4,6,16,18], [1,5,7,19], [6,8,20,22], [1,7,9,23],
+        [2,8,10,24], [9,11,25,27], [2,10,12,28], [3,11,13,29], [12,14,30,32], [3,13,15,33], [4,14,16,34], [5,15,17,35],
+        [16,18,36,38], [5,17,19,39], [6,18,20,40], [7,19,21,41], [20,22,42,44], [7,21,23,45], [8,22,24,46], [9,23,25,47],
+        [10,24,26,48], [25,27,49,51], [10,26,28,52], [11,27,29,53], [12,28,30,54], [13,29,31,55], [32,30,56,58], [13,31,33,59],
+        [14,32,34,60], [15,33,35,61], [16,34,36,62], [17,35,37,63], [36,38,64,0], [17,37,39,0], [18,38,40,0], [19,39,41,0],
+        [20,40,42,0], [21,41,43,0], [42,44,0,0], [21,43,45,0], [22,44,46,0], [23,45,47,0], [24,46,48,0], [25,47,49,0],
+        [26,48,50,0], [49,51,0,0], [26,50,52,0], [27,51,53,0], [28,52,54,0], [29,53,55,0], [30,54,56,0], [31,55,57,0],
+        [56,58,0,0], [31,57,59,0], [32,58,60,0], [33,59,61,0], [34,60,62,0], [35,61,63,0], [36,62,64,0], [37,63,0,0],
+    ];
+
+    fn sorted_neighbors(square: u64) -> Vec<u64> {
+        let mut result: Vec<u64> = neighbors(DEFAULT_BOARD_SIDE, square).collect();
+        result.sort();
+        result
+    }
+
+    #[test]
+    fn test_readme_layout() {
+        let side = DEFAULT_BOARD_SIDE;
+        assert_eq!(position(side, 0), Some((3, 3)));
+        assert_eq!(position(side, 1), Some((3, 4)));
+        assert_eq!(position(side, 42), Some((0, 0)));
+        assert_eq!(position(side, 49), Some((0, 7)));
+        assert_eq!(position(side, 56), Some((7, 7)));
+        assert_eq!(position(side, 63), Some((7, 0)));
+        assert_eq!(position(side, 64), None);
+        assert_eq!(square_at(side, 8, 0), None);
+    }
+
+    #[test]
+    fn test_matches_legacy_table() {
+        for (i, row) in LEGACY_ATTACKABLE.iter().enumerate() {
+            let mut expected: Vec<u64> = row.iter().filter(|n| **n != 0).map(|n| n - 1).collect();
+            expected.sort();
+            assert_eq!(sorted_neighbors(i as u64), expected, "square {}", i);
+        }
+    }
+
+    #[test]
+    fn test_symmetry() {
+        for side in 1..=MAX_BOARD_SIDE {
+            for square in 0..side * side {
+                for neighbor in neighbors(side, square) {
+                    assert!(is_neighbor(side, neighbor, square), "{} next to {} but not back", square, neighbor);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_every_position_used_once() {
+        for side in 1..=MAX_BOARD_SIDE {
+            let mut seen = vec![false; (side * side) as usize];
+            for square in 0..side * side {
+                let (row, col) = position(side, square).unwrap();
+                assert!(row < side && col < side);
+                assert!(!seen[(row * side + col) as usize]);
+                seen[(row * side + col) as usize] = true;
+                assert_eq!(square_at(side, row, col), Some(square));
+            }
+        }
+    }
+
+    #[test]
+    fn test_neighbor_counts() {
+        for side in 2..=MAX_BOARD_SIDE {
+            for square in 0..side * side {
+                let (row, col) = position(side, square).unwrap();
+                let edges = (row == 0 || row == side - 1) as usize + (col == 0 || col == side - 1) as usize;
+                assert_eq!(neighbors(side, square).count(), 4 - edges, "side {} square {}", side, square);
+                assert!(!is_neighbor(side, square, square));
+            }
+            assert_eq!(neighbors(side, side * side).count(), 0);
+        }
+    }
+
+    #[test]
+    fn test_roll_under() {
+        // Thresholds of the 64 square board
+        assert_eq!(roll_under(64, 0), 55);
+        assert_eq!(roll_under(64, 1), 54);
+        assert_eq!(roll_under(64, 16), 54);
+        assert_eq!(roll_under(64, 17), 53);
+        assert_eq!(roll_under(64, 32), 53);
+        assert_eq!(roll_under(64, 33), 52);
+        assert_eq!(roll_under(64, 48), 52);
+        assert_eq!(roll_under(64, 49), 51);
+        assert_eq!(roll_under(64, 63), 51);
+        assert_eq!(roll_under(16, 0), 55);
+        assert_eq!(roll_under(16, 15), 51);
+    }
+}
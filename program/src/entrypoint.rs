@@ -2,10 +2,11 @@ use solana_sdk::{
     account_info::{next_account_info, AccountInfo},
     entrypoint,
     entrypoint::ProgramResult,
+    program_error::PrintProgramError,
     pubkey::Pubkey,
 };
 
-use crate::processor::Processor;
+use crate::{error::SixtyFourGameError, processor::Processor};
 
 entrypoint!(process_instruction);
 fn process_instruction(
@@ -13,5 +14,10 @@ fn process_instruction(
     accounts: &[AccountInfo],
     instruction_data: &[u8],
 ) -> ProgramResult {
-    Processor::process(program_id, accounts, instruction_data)
+    if let Err(error) = Processor::process(program_id, accounts, instruction_data) {
+        // Log the SixtyFourGameError message before failing
+        error.print::<SixtyFourGameError>();
+        return Err(error);
+    }
+    Ok(())
 }
@@ -1,20 +1,200 @@
-
 use thiserror::Error;
 use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
 use solana_sdk::{decode_error::DecodeError};
 use solana_sdk::{
-    program_error::ProgramError,
+    msg,
+    program_error::{PrintProgramError, ProgramError},
 };
 
 /// Errors that may be returned by SixtyFourGame
+///
+/// Codes are part of the client interface, new variants are only ever appended.
 #[derive(Clone, Debug, Eq, Error, FromPrimitive, PartialEq)]
 pub enum SixtyFourGameError {
     /// Invalid instruction number passed in.
     #[error("Invalid instruction")]
-    InvalidInstruction,
+    InvalidInstruction = 0,
     /// The deserialization of the account returned something besides State::Mint.
     #[error("Deserialized account is not an SPL Token mint")]
-    ExpectedMint,
+    ExpectedMint = 1,
+    /// Signer is not the AuctionConfig admin.
+    #[error("Signer is not the auction admin")]
+    NotAdmin = 2,
+    /// InitiateAuction called on an auction that already started.
+    #[error("Auction already started")]
+    AuctionAlreadyStarted = 3,
+    /// InitiateAuction called with zero or inconsistent parameters.
+    #[error("Invalid auction parameters")]
+    InvalidAuctionParameters = 4,
+    /// Auction has not started, has ended or has started minting.
+    #[error("Auction is not active")]
+    AuctionNotActive = 5,
+    /// Auction list has reached max_bid_count.
+    #[error("Auction has reached the max bid count")]
+    AuctionFull = 6,
+    /// Instruction is only allowed once the auction has ended.
+    #[error("Auction is still running")]
+    AuctionStillRunning = 7,
+    /// Every game square has already been minted.
+    #[error("All game squares have been minted")]
+    AllSquaresMinted = 8,
+    /// Refunds are only paid once every game square has been minted.
+    #[error("Auction has not been resolved")]
+    AuctionNotResolved = 9,
+    /// Bid number is past the end of the auction list.
+    #[error("Bid number does not exist")]
+    InvalidBidNumber = 10,
+    /// Signer or account does not match the BidEntry bidder.
+    #[error("Account is not the bidder of this bid")]
+    NotBidder = 11,
+    /// BidEntry was already cancelled or refunded.
+    #[error("Bid is no longer active")]
+    BidNotActive = 12,
+    /// BidEntry won a game square and cannot be refunded.
+    #[error("Bid won a game square")]
+    BidWon = 13,
+    /// MintNFT called for an account that is not the highest bidder.
+    #[error("Account is not the highest bidder")]
+    NotHighestBidder = 14,
+    /// Attacker and defender squares are not neighbors.
+    #[error("Squares are not neighbors")]
+    NotNeighbors = 15,
+    /// Attacker and defender squares are on the same team.
+    #[error("Squares are on the same team")]
+    SameTeam = 16,
+    /// Attacker square is not active.
+    #[error("Attacker square is not active")]
+    AttackerInactive = 17,
+    /// Defender square is not active.
+    #[error("Defender square is not active")]
+    DefenderInactive = 18,
+    /// Signer or account does not match the ActivePlayer owner.
+    #[error("Account is not the owner of this square")]
+    NotSquareOwner = 19,
+    /// ClaimPrize called before a team won.
+    #[error("Game is not over")]
+    GameNotOver = 20,
+    /// Prize for this square was already claimed.
+    #[error("Prize already claimed")]
+    PrizeAlreadyClaimed = 21,
+    /// Account is not the AuctionConfig program address.
+    #[error("Invalid auction config account")]
+    InvalidAuctionConfigAccount = 22,
+    /// Account is not the treasury program address.
+    #[error("Invalid treasury account")]
+    InvalidTreasuryAccount = 23,
+    /// EndPlay called after Showdown started.
+    #[error("Showdown has started, squares cannot be withdrawn")]
+    ShowdownStarted = 24,
+    /// PurgeInactive called before Showdown started.
+    #[error("Showdown has not started")]
+    ShowdownNotStarted = 25,
+    /// PurgeInactive called for a square that is in play.
+    #[error("Square is active")]
+    SquareActive = 26,
+    /// Square was purged during Showdown and is out of the game.
+    #[error("Square has been purged")]
+    SquarePurged = 27,
+    /// A team already won the game.
+    #[error("Game is over")]
+    GameOver = 28,
+    /// Account is not the GameState program address.
+    #[error("Invalid game state account")]
+    InvalidGameStateAccount = 29,
+    /// Square number is past the last minted square.
+    #[error("Square does not exist")]
+    InvalidSquareNumber = 30,
+    /// Square already committed an attack that has not been resolved.
+    #[error("Square has a pending attack")]
+    AttackPending = 31,
+    /// ResolveAttack called for a square without a committed attack.
+    #[error("Square has no pending attack")]
+    NoPendingAttack = 32,
+    /// ResolveAttack called before the slot hash of the attack exists.
+    #[error("Attack cannot be resolved yet")]
+    AttackNotReady = 33,
+    /// Revealed secret does not hash to the attack commitment.
+    #[error("Secret does not match the attack commitment")]
+    InvalidAttackSecret = 34,
+    /// Account is not the SlotHashes sysvar.
+    #[error("Invalid slot hashes account")]
+    InvalidSlotHashesAccount = 35,
+    /// Slot hash of the attack is no longer in the SlotHashes sysvar.
+    #[error("Slot hash of the attack is not available")]
+    SlotHashUnavailable = 36,
+    /// Mint account is not the GameSquare mint.
+    #[error("Mint does not match the game square")]
+    InvalidSquareMint = 37,
+    /// Token account is not the program token account holding the square's NFT.
+    #[error("Invalid program token account")]
+    InvalidProgramTokenAccount = 38,
+    /// EndPlay called for a square that is not deposited.
+    #[error("Square is not active")]
+    SquareNotActive = 39,
+    /// Instruction needs every square minted and the game in play.
+    #[error("Game has not started")]
+    GameNotStarted = 40,
+    /// EndPlay called by a winner before claiming the prize.
+    #[error("Prize has not been claimed")]
+    PrizeNotClaimed = 41,
+    /// ClaimPrize called for a square that is not on the winning team.
+    #[error("Square is not on the winning team")]
+    NotWinningTeam = 42,
+    /// Account is not the bid index saved in the AuctionConfig.
+    #[error("Invalid bid index account")]
+    InvalidBidIndexAccount = 43,
+    /// Account is not the auction list page program address holding the bid.
+    #[error("Invalid auction list account")]
+    InvalidAuctionListAccount = 44,
+    /// Bid amount is below the AuctionConfig min_bid_lamports.
+    #[error("Bid is below the minimum bid")]
+    BidBelowMinimum = 45,
+    /// Bid amount is below the reserve price of the rank it would enter at.
+    #[error("Bid is below the reserve price")]
+    BidBelowReserve = 46,
+    /// Bid does not beat the last winning rank by min_increment_lamports.
+    #[error("Bid is below the minimum increment")]
+    BidBelowIncrement = 47,
+    /// Bid or CommitBid used in an auction taking the other kind of bid.
+    #[error("Bid does not match the auction mode")]
+    InvalidBidMode = 48,
+    /// RevealBid called outside the reveal window.
+    #[error("Reveal is not active")]
+    RevealNotActive = 49,
+    /// Revealed amount and salt do not match the commitment or exceed the deposit.
+    #[error("Invalid bid reveal")]
+    InvalidBidReveal = 50,
+    /// Refund claimed for a sealed bid that was never revealed and is forfeited.
+    #[error("Unrevealed bid is forfeited")]
+    BidForfeited = 51,
+    /// BuySquare called with a max_price below the current Dutch auction price.
+    #[error("Square price is above the max price")]
+    PriceAboveMax = 52,
+    /// Token account is not the bid escrow or a token account of the bid mint owned by the user.
+    #[error("Invalid bid token account")]
+    InvalidBidTokenAccount = 53,
+    /// IncreaseBid called with zero additional_lamports.
+    #[error("Bid is not increased")]
+    BidNotIncreased = 54,
+    /// Bid amount does not fit in a u64 once increased.
+    #[error("Bid amount overflow")]
+    BidAmountOverflow = 55,
+    /// Account is not the program owned game squares list saved in the AuctionConfig.
+    #[error("Invalid game squares list account")]
+    InvalidGameSquaresListAccount = 56,
+    /// Account is not the program owned AuctionInfo saved in the AuctionConfig.
+    #[error("Invalid auction info account")]
+    InvalidAuctionInfoAccount = 57,
+    /// Account is not the program owned active players list saved in the AuctionConfig.
+    #[error("Invalid active players list account")]
+    InvalidActivePlayersListAccount = 58,
+    /// Mint authority account is not the program's mint authority address.
+    #[error("Invalid mint authority account")]
+    InvalidMintAuthority = 59,
+    /// PurgeInactive would leave no square in the game to win the prize.
+    #[error("Last square in the game")]
+    LastSquareInGame = 60,
 }
 
 impl From<SixtyFourGameError> for ProgramError {
@@ -28,3 +208,12 @@ impl<T> DecodeError<T> for SixtyFourGameError {
         "SixtyFourGame Error"
     }
 }
+
+impl PrintProgramError for SixtyFourGameError {
+    fn print<E>(&self)
+    where
+        E: 'static + std::error::Error + DecodeError<E> + PrintProgramError + FromPrimitive,
+    {
+        msg!(&self.to_string());
+    }
+}
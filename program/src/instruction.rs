@@ -1,24 +1,120 @@
 use std::convert::TryInto;
+use std::mem::size_of;
 use solana_sdk::{
+    hash::Hash,
+    instruction::{AccountMeta, Instruction},
     program_error::ProgramError,
+    pubkey::Pubkey,
+    system_program,
+    sysvar,
 };
 use crate::error::SixtyFourGameError::InvalidInstruction;
-use solana_sdk::{
-    msg,
+use crate::state::AuctionMode;
+use crate::util::{
+    AUCTION_CONFIG_SEED, TREASURY_SEED, GAME_STATE_SEED, PROGRAM_TOKEN_ACCOUNT_SEED, PROGRAM_TOKEN_OWNER_SEED,
+    AUCTION_LIST_SEED, BID_ESCROW_SEED, auction_list_page,
 };
+/// Auction parameters of an InititateAuction, saved into the AuctionConfig
+#[derive(Clone, Debug, PartialEq)]
+pub struct InitiateAuctionParams {
+    pub auction_end_slot: u64,
+    pub max_bid_count: u64,
+    pub board_side: u64,
+    pub team_count: u64,
+    pub starting_health: u64,
+    pub showdown_delay_slots: u64,
+    pub soft_close_slots: u64,
+    pub extension_slots: u64,
+    pub max_extension_slots: u64,
+    pub min_bid_lamports: u64,
+    pub reserve_price_lamports: u64,
+    pub reserve_step_lamports: u64,
+    pub min_increment_lamports: u64,
+    pub top_up_keeps_priority: bool,
+    pub reveal_slots: u64,
+    pub forfeit_unrevealed: bool,
+    pub auction_mode: AuctionMode,
+    pub dutch_start_price_lamports: u64,
+    pub dutch_price_step_lamports: u64,
+    pub bid_mint: Pubkey,
+}
+
+/// Game accounts allocated by the admin before InititateAuction, shared by the play instructions
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GameAccounts {
+    pub auction_info: Pubkey,
+    pub bid_index: Pubkey,
+    pub all_game_squares_list: Pubkey,
+    pub active_players_list: Pubkey,
+}
+
+impl InitiateAuctionParams {
+    /// Unpacks the InititateAuction data following the tag
+    fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        Ok(Self {
+            auction_end_slot: SixtyFourGameInstruction::unpack_amount(input, 0)?,
+            max_bid_count: SixtyFourGameInstruction::unpack_amount(input, 8)?,
+            board_side: SixtyFourGameInstruction::unpack_amount(input, 16)?,
+            team_count: SixtyFourGameInstruction::unpack_amount(input, 24)?,
+            starting_health: SixtyFourGameInstruction::unpack_amount(input, 32)?,
+            showdown_delay_slots: SixtyFourGameInstruction::unpack_amount(input, 40)?,
+            soft_close_slots: SixtyFourGameInstruction::unpack_amount(input, 48)?,
+            extension_slots: SixtyFourGameInstruction::unpack_amount(input, 56)?,
+            max_extension_slots: SixtyFourGameInstruction::unpack_amount(input, 64)?,
+            min_bid_lamports: SixtyFourGameInstruction::unpack_amount(input, 72)?,
+            reserve_price_lamports: SixtyFourGameInstruction::unpack_amount(input, 80)?,
+            reserve_step_lamports: SixtyFourGameInstruction::unpack_amount(input, 88)?,
+            min_increment_lamports: SixtyFourGameInstruction::unpack_amount(input, 96)?,
+            top_up_keeps_priority: SixtyFourGameInstruction::unpack_bool(input, 104)?,
+            reveal_slots: SixtyFourGameInstruction::unpack_amount(input, 105)?,
+            forfeit_unrevealed: SixtyFourGameInstruction::unpack_bool(input, 113)?,
+            auction_mode: SixtyFourGameInstruction::unpack_auction_mode(input, 114)?,
+            dutch_start_price_lamports: SixtyFourGameInstruction::unpack_amount(input, 115)?,
+            dutch_price_step_lamports: SixtyFourGameInstruction::unpack_amount(input, 123)?,
+            bid_mint: SixtyFourGameInstruction::unpack_pubkey(input, 131)?,
+        })
+    }
 
+    /// Appends the packed parameters to an InititateAuction buffer
+    fn pack_into(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.auction_end_slot.to_le_bytes());
+        buf.extend_from_slice(&self.max_bid_count.to_le_bytes());
+        buf.extend_from_slice(&self.board_side.to_le_bytes());
+        buf.extend_from_slice(&self.team_count.to_le_bytes());
+        buf.extend_from_slice(&self.starting_health.to_le_bytes());
+        buf.extend_from_slice(&self.showdown_delay_slots.to_le_bytes());
+        buf.extend_from_slice(&self.soft_close_slots.to_le_bytes());
+        buf.extend_from_slice(&self.extension_slots.to_le_bytes());
+        buf.extend_from_slice(&self.max_extension_slots.to_le_bytes());
+        buf.extend_from_slice(&self.min_bid_lamports.to_le_bytes());
+        buf.extend_from_slice(&self.reserve_price_lamports.to_le_bytes());
+        buf.extend_from_slice(&self.reserve_step_lamports.to_le_bytes());
+        buf.extend_from_slice(&self.min_increment_lamports.to_le_bytes());
+        buf.push(self.top_up_keeps_priority as u8);
+        buf.extend_from_slice(&self.reveal_slots.to_le_bytes());
+        buf.push(self.forfeit_unrevealed as u8);
+        buf.push(self.auction_mode as u8);
+        buf.extend_from_slice(&self.dutch_start_price_lamports.to_le_bytes());
+        buf.extend_from_slice(&self.dutch_price_step_lamports.to_le_bytes());
+        buf.extend_from_slice(self.bid_mint.as_ref());
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
 pub enum SixtyFourGameInstruction {
 
-    /// InititateAuction - auction_end_slot - sets the auction end slot
+    /// InititateAuction - auction_end_slot and auction parameters - admin only, sets the
+    /// auction end slot and saves the parameters into the AuctionConfig
     InititateAuction {
-        auction_end_slot: u64
+        params: InitiateAuctionParams,
     },
     /// Bid - amount  - adds BidEntry to AuctionList
     Bid {
         amount: u64,
     },
-    /// CancelBid - removes BidEntry from AuctionList
+    /// CancelBid - bid_number - cancels own BidEntry in AuctionList and refunds it
     CancelBid {
+        bid_number: u64,
     },
     /// MintNFT - creates NFT after auction
     MintNFT {
@@ -31,16 +127,63 @@ pub enum SixtyFourGameInstruction {
     EndPlay {
         square: u64,
     },
-    /// Attack - amount and from/to squares - attacks neighboring square
+    /// Attack - amount, from/to squares and sha256 commitment of a secret - commits an attack
+    /// on a neighboring square, resolved by ResolveAttack
     Attack {
         amount: u64,
         from_square: u64,
         to_square: u64,
+        commitment: Hash,
     },
     /// ClaimPrize - square - once game is over send winner the prize
     ClaimPrize {
         square: u64,
     },
+    /// ClaimRefund - bid_number - once minting ended refund a losing BidEntry
+    ClaimRefund {
+        bid_number: u64,
+    },
+    /// SetAdmin - new_admin - admin only, rotates the AuctionConfig admin
+    SetAdmin {
+        new_admin: Pubkey,
+    },
+    /// RenounceAdmin - admin only, removes the AuctionConfig admin for good
+    RenounceAdmin {
+    },
+    /// PurgeInactive - square - anyone, once Showdown started removes a square that is not in play
+    PurgeInactive {
+        square: u64,
+    },
+    /// ResolveAttack - from_square and secret - rolls a committed attack with the secret and
+    /// the hash of a later slot, anyone can resolve an attack that was not revealed in time
+    ResolveAttack {
+        from_square: u64,
+        secret: [u8; 32],
+    },
+    /// IncreaseBid - bid_number and additional_lamports - adds to own active BidEntry, keeps its
+    /// tie-breaking priority only if the AuctionConfig top_up_keeps_priority is set
+    IncreaseBid {
+        bid_number: u64,
+        additional_lamports: u64,
+    },
+    /// CommitBid - deposit and commitment - sealed bid auctions only, adds a sealed BidEntry
+    /// holding the deposit and util::bid_commitment of the amount and a salt
+    CommitBid {
+        deposit: u64,
+        commitment: Hash,
+    },
+    /// RevealBid - bid_number, amount and salt - reveals own sealed BidEntry after the auction
+    /// end, ranks the amount and refunds the rest of the deposit
+    RevealBid {
+        bid_number: u64,
+        amount: u64,
+        salt: [u8; 32],
+    },
+    /// BuySquare - max_price - Dutch auctions only, buys and mints the next square at the
+    /// current price, fails if the price is above max_price
+    BuySquare {
+        max_price: u64,
+    },
 }
 
 impl SixtyFourGameInstruction {
@@ -49,12 +192,14 @@ impl SixtyFourGameInstruction {
         let (tag, rest) = input.split_first().ok_or(InvalidInstruction)?;
         Ok(match tag {
             0 => Self::InititateAuction {
-                auction_end_slot: Self::unpack_amount(rest, 0)?,
+                params: InitiateAuctionParams::unpack(rest)?,
             },
             1 => Self::Bid {
                 amount: Self::unpack_amount(rest, 0)?,
             },
-            2 => Self::CancelBid {},
+            2 => Self::CancelBid {
+                bid_number: Self::unpack_amount(rest, 0)?,
+            },
             3 => Self::MintNFT {},
             4 => Self::InitiatePlay {
                 square: Self::unpack_amount(rest, 0)?,
@@ -66,14 +211,127 @@ impl SixtyFourGameInstruction {
                 amount: Self::unpack_amount(rest, 0)?,
                 from_square: Self::unpack_amount(rest, 8)?,
                 to_square: Self::unpack_amount(rest, 16)?,
+                commitment: Hash::new_from_array(Self::unpack_bytes(rest, 24)?),
             },
             7 => Self::ClaimPrize {
                 square: Self::unpack_amount(rest, 0)?,
             },
+            8 => Self::ClaimRefund {
+                bid_number: Self::unpack_amount(rest, 0)?,
+            },
+            9 => Self::SetAdmin {
+                new_admin: Self::unpack_pubkey(rest, 0)?,
+            },
+            10 => Self::RenounceAdmin {},
+            11 => Self::PurgeInactive {
+                square: Self::unpack_amount(rest, 0)?,
+            },
+            12 => Self::ResolveAttack {
+                from_square: Self::unpack_amount(rest, 0)?,
+                secret: Self::unpack_bytes(rest, 8)?,
+            },
+            13 => Self::IncreaseBid {
+                bid_number: Self::unpack_amount(rest, 0)?,
+                additional_lamports: Self::unpack_amount(rest, 8)?,
+            },
+            14 => Self::CommitBid {
+                deposit: Self::unpack_amount(rest, 0)?,
+                commitment: Hash::new_from_array(Self::unpack_bytes(rest, 8)?),
+            },
+            15 => Self::RevealBid {
+                bid_number: Self::unpack_amount(rest, 0)?,
+                amount: Self::unpack_amount(rest, 8)?,
+                salt: Self::unpack_bytes(rest, 16)?,
+            },
+            16 => Self::BuySquare {
+                max_price: Self::unpack_amount(rest, 0)?,
+            },
             _ => return Err(InvalidInstruction.into()),
         })
     }
 
+    /// Packs a SixtyFourGameInstruction into a byte buffer
+    pub fn pack(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(size_of::<Self>());
+        match self {
+            Self::InititateAuction { params } => {
+                buf.push(0);
+                params.pack_into(&mut buf);
+            }
+            Self::Bid { amount } => {
+                buf.push(1);
+                buf.extend_from_slice(&amount.to_le_bytes());
+            }
+            Self::CancelBid { bid_number } => {
+                buf.push(2);
+                buf.extend_from_slice(&bid_number.to_le_bytes());
+            }
+            Self::MintNFT {} => {
+                buf.push(3);
+            }
+            Self::InitiatePlay { square } => {
+                buf.push(4);
+                buf.extend_from_slice(&square.to_le_bytes());
+            }
+            Self::EndPlay { square } => {
+                buf.push(5);
+                buf.extend_from_slice(&square.to_le_bytes());
+            }
+            Self::Attack { amount, from_square, to_square, commitment } => {
+                buf.push(6);
+                buf.extend_from_slice(&amount.to_le_bytes());
+                buf.extend_from_slice(&from_square.to_le_bytes());
+                buf.extend_from_slice(&to_square.to_le_bytes());
+                buf.extend_from_slice(commitment.as_ref());
+            }
+            Self::ClaimPrize { square } => {
+                buf.push(7);
+                buf.extend_from_slice(&square.to_le_bytes());
+            }
+            Self::ClaimRefund { bid_number } => {
+                buf.push(8);
+                buf.extend_from_slice(&bid_number.to_le_bytes());
+            }
+            Self::SetAdmin { new_admin } => {
+                buf.push(9);
+                buf.extend_from_slice(new_admin.as_ref());
+            }
+            Self::RenounceAdmin {} => {
+                buf.push(10);
+            }
+            Self::PurgeInactive { square } => {
+                buf.push(11);
+                buf.extend_from_slice(&square.to_le_bytes());
+            }
+            Self::ResolveAttack { from_square, secret } => {
+                buf.push(12);
+                buf.extend_from_slice(&from_square.to_le_bytes());
+                buf.extend_from_slice(secret);
+            }
+            Self::IncreaseBid { bid_number, additional_lamports } => {
+                buf.push(13);
+                buf.extend_from_slice(&bid_number.to_le_bytes());
+                buf.extend_from_slice(&additional_lamports.to_le_bytes());
+            }
+            Self::CommitBid { deposit, commitment } => {
+                buf.push(14);
+                buf.extend_from_slice(&deposit.to_le_bytes());
+                buf.extend_from_slice(commitment.as_ref());
+            }
+            Self::RevealBid { bid_number, amount, salt } => {
+                buf.push(15);
+                buf.extend_from_slice(&bid_number.to_le_bytes());
+                buf.extend_from_slice(&amount.to_le_bytes());
+                buf.extend_from_slice(salt);
+            }
+            Self::BuySquare { max_price } => {
+                buf.push(16);
+                buf.extend_from_slice(&max_price.to_le_bytes());
+            }
+        }
+        buf
+    }
+
     fn unpack_amount(input: &[u8], offet: usize) -> Result<u64, ProgramError> {
         let amount = input
             .get(offet..offet + 8)
@@ -83,12 +341,609 @@ impl SixtyFourGameInstruction {
         Ok(amount)
     }
 
-    fn unpack_amount_32(input: &[u8]) -> Result<u32, ProgramError> {
-        let amount = input
-            .get(..4)
+    fn unpack_pubkey(input: &[u8], offset: usize) -> Result<Pubkey, ProgramError> {
+        let pubkey = input
+            .get(offset..offset + 32)
+            .map(Pubkey::new)
+            .ok_or(InvalidInstruction)?;
+        Ok(pubkey)
+    }
+
+    fn unpack_bytes(input: &[u8], offset: usize) -> Result<[u8; 32], ProgramError> {
+        let bytes = input
+            .get(offset..offset + 32)
             .and_then(|slice| slice.try_into().ok())
-            .map(u32::from_le_bytes)
             .ok_or(InvalidInstruction)?;
-        Ok(amount)
+        Ok(bytes)
+    }
+
+    fn unpack_bool(input: &[u8], offset: usize) -> Result<bool, ProgramError> {
+        match input.get(offset) {
+            Some(0) => Ok(false),
+            Some(1) => Ok(true),
+            _ => Err(InvalidInstruction.into()),
+        }
+    }
+
+    fn unpack_auction_mode(input: &[u8], offset: usize) -> Result<AuctionMode, ProgramError> {
+        match input.get(offset) {
+            Some(0) => Ok(AuctionMode::English),
+            Some(1) => Ok(AuctionMode::Dutch),
+            _ => Err(InvalidInstruction.into()),
+        }
+    }
+}
+
+fn auction_config_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[AUCTION_CONFIG_SEED], program_id).0
+}
+
+fn treasury_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[TREASURY_SEED], program_id).0
+}
+
+fn game_state_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[GAME_STATE_SEED], program_id).0
+}
+
+/// Auction list page holding the BidEntry of bid_number
+pub fn auction_list_address(program_id: &Pubkey, bid_number: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[AUCTION_LIST_SEED, &auction_list_page(bid_number).to_le_bytes()],
+        program_id,
+    ).0
+}
+
+/// Program token account escrowing bids when the auction has a bid mint
+pub fn bid_escrow_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[BID_ESCROW_SEED], program_id).0
+}
+
+fn program_token_owner_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[PROGRAM_TOKEN_OWNER_SEED], program_id).0
+}
+
+/// Program token account a square's NFT is deposited into by InitiatePlay
+pub fn program_token_address(program_id: &Pubkey, square: u64, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[PROGRAM_TOKEN_ACCOUNT_SEED, &square.to_le_bytes(), mint.as_ref()],
+        program_id,
+    ).0
+}
+
+/// Creates an InititateAuction instruction, co-signed by the program account
+pub fn initiate_auction(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    auction_info: &Pubkey,
+    bid_index: &Pubkey,
+    all_game_squares_list: &Pubkey,
+    active_players_list: &Pubkey,
+    params: InitiateAuctionParams,
+) -> Instruction {
+    let bid_mint = params.bid_mint;
+    let data = SixtyFourGameInstruction::InititateAuction { params }.pack();
+    let mut accounts = vec![
+        AccountMeta::new(*admin, true),
+        AccountMeta::new(*auction_info, false),
+        AccountMeta::new(auction_config_address(program_id), false),
+        AccountMeta::new(treasury_address(program_id), false),
+        AccountMeta::new(game_state_address(program_id), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(*bid_index, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(*program_id, true),
+        AccountMeta::new_readonly(*all_game_squares_list, false),
+        AccountMeta::new_readonly(*active_players_list, false),
+    ];
+    if bid_mint != Pubkey::default() {
+        accounts.extend(vec![
+            AccountMeta::new_readonly(bid_mint, false),
+            AccountMeta::new(bid_escrow_address(program_id), false),
+            AccountMeta::new_readonly(program_token_owner_address(program_id), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ]);
+    }
+    Instruction { program_id: *program_id, accounts, data }
+}
+
+/// Appends the bid token accounts to an instruction moving bid funds in an auction with a bid
+/// mint: the user's token account of the bid mint, the bid escrow, its owner and the token program
+pub fn with_bid_token_accounts(program_id: &Pubkey, mut instruction: Instruction, user_token_account: &Pubkey) -> Instruction {
+    instruction.accounts.extend(vec![
+        AccountMeta::new(*user_token_account, false),
+        AccountMeta::new(bid_escrow_address(program_id), false),
+        AccountMeta::new_readonly(program_token_owner_address(program_id), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ]);
+    instruction
+}
+
+/// Creates a Bid instruction, bid_number is the AuctionInfo bid_count the bid is saved at
+pub fn bid(
+    program_id: &Pubkey,
+    bidder: &Pubkey,
+    amount: u64,
+    bid_number: u64,
+    auction_info: &Pubkey,
+    bid_index: &Pubkey,
+) -> Instruction {
+    let data = SixtyFourGameInstruction::Bid { amount }.pack();
+    let accounts = vec![
+        AccountMeta::new(*bidder, true),
+        AccountMeta::new(auction_list_address(program_id, bid_number), false),
+        AccountMeta::new(treasury_address(program_id), false),
+        AccountMeta::new(*auction_info, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(auction_config_address(program_id), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(game_state_address(program_id), false),
+        AccountMeta::new(*bid_index, false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ];
+    Instruction { program_id: *program_id, accounts, data }
+}
+
+/// Creates a CancelBid instruction
+pub fn cancel_bid(
+    program_id: &Pubkey,
+    bidder: &Pubkey,
+    bid_number: u64,
+    auction_info: &Pubkey,
+    bid_index: &Pubkey,
+) -> Instruction {
+    let data = SixtyFourGameInstruction::CancelBid { bid_number }.pack();
+    let accounts = vec![
+        AccountMeta::new(*bidder, true),
+        AccountMeta::new(auction_list_address(program_id, bid_number), false),
+        AccountMeta::new(treasury_address(program_id), false),
+        AccountMeta::new(*auction_info, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(auction_config_address(program_id), false),
+        AccountMeta::new_readonly(game_state_address(program_id), false),
+        AccountMeta::new(*bid_index, false),
+    ];
+    Instruction { program_id: *program_id, accounts, data }
+}
+
+/// Creates a MintNFT instruction for the winning bid_number, mint and token_account must already be allocated
+pub fn mint_nft(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    bidder: &Pubkey,
+    bid_number: u64,
+    mint: &Pubkey,
+    token_account: &Pubkey,
+    game_accounts: &GameAccounts,
+) -> Instruction {
+    let data = SixtyFourGameInstruction::MintNFT {}.pack();
+    let (mint_pda, _) = Pubkey::find_program_address(&[b"mint"], program_id);
+    let accounts = vec![
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(*bidder, false),
+        AccountMeta::new(auction_list_address(program_id, bid_number), false),
+        AccountMeta::new(game_accounts.auction_info, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new(*mint, false),
+        AccountMeta::new(*token_account, false),
+        AccountMeta::new_readonly(mint_pda, false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(game_accounts.all_game_squares_list, false),
+        AccountMeta::new_readonly(treasury_address(program_id), false),
+        AccountMeta::new_readonly(auction_config_address(program_id), false),
+        AccountMeta::new(game_state_address(program_id), false),
+        AccountMeta::new_readonly(game_accounts.bid_index, false),
+    ];
+    Instruction { program_id: *program_id, accounts, data }
+}
+
+/// Creates an InitiatePlay instruction
+pub fn initiate_play(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    square: u64,
+    game_square_token_account: &Pubkey,
+    mint: &Pubkey,
+    game_accounts: &GameAccounts,
+) -> Instruction {
+    let data = SixtyFourGameInstruction::InitiatePlay { square }.pack();
+    let (program_token_pda, _) = Pubkey::find_program_address(&[PROGRAM_TOKEN_OWNER_SEED], program_id);
+    let accounts = vec![
+        AccountMeta::new(*player, true),
+        AccountMeta::new(*game_square_token_account, false),
+        AccountMeta::new_readonly(game_accounts.auction_info, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new_readonly(program_token_pda, false),
+        AccountMeta::new(program_token_address(program_id, square, mint), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(game_accounts.active_players_list, false),
+        AccountMeta::new_readonly(treasury_address(program_id), false),
+        AccountMeta::new_readonly(game_accounts.all_game_squares_list, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(auction_config_address(program_id), false),
+        AccountMeta::new_readonly(game_state_address(program_id), false),
+    ];
+    Instruction { program_id: *program_id, accounts, data }
+}
+
+/// Creates an EndPlay instruction, user_token_account must already be allocated
+pub fn end_play(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    square: u64,
+    user_token_account: &Pubkey,
+    mint: &Pubkey,
+    game_accounts: &GameAccounts,
+) -> Instruction {
+    let data = SixtyFourGameInstruction::EndPlay { square }.pack();
+    let (program_token_pda, _) = Pubkey::find_program_address(&[PROGRAM_TOKEN_OWNER_SEED], program_id);
+    let accounts = vec![
+        AccountMeta::new(*player, true),
+        AccountMeta::new(*user_token_account, false),
+        AccountMeta::new(program_token_address(program_id, square, mint), false),
+        AccountMeta::new_readonly(game_accounts.auction_info, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new_readonly(program_token_pda, false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(game_accounts.active_players_list, false),
+        AccountMeta::new_readonly(treasury_address(program_id), false),
+        AccountMeta::new_readonly(auction_config_address(program_id), false),
+        AccountMeta::new(game_state_address(program_id), false),
+        AccountMeta::new_readonly(game_accounts.all_game_squares_list, false),
+    ];
+    Instruction { program_id: *program_id, accounts, data }
+}
+
+/// Creates an Attack instruction, commitment is util::attack_commitment of the secret
+/// later passed to ResolveAttack
+pub fn attack(
+    program_id: &Pubkey,
+    attacker: &Pubkey,
+    amount: u64,
+    from_square: u64,
+    to_square: u64,
+    commitment: Hash,
+    game_accounts: &GameAccounts,
+) -> Instruction {
+    let data = SixtyFourGameInstruction::Attack { amount, from_square, to_square, commitment }.pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(*attacker, true),
+        AccountMeta::new_readonly(game_accounts.auction_info, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new(game_accounts.active_players_list, false),
+        AccountMeta::new_readonly(game_accounts.all_game_squares_list, false),
+        AccountMeta::new_readonly(auction_config_address(program_id), false),
+        AccountMeta::new_readonly(game_state_address(program_id), false),
+    ];
+    Instruction { program_id: *program_id, accounts, data }
+}
+
+/// Creates a ResolveAttack instruction
+pub fn resolve_attack(
+    program_id: &Pubkey,
+    from_square: u64,
+    secret: [u8; 32],
+    auction_info: &Pubkey,
+    active_players_list: &Pubkey,
+    all_game_squares_list: &Pubkey,
+) -> Instruction {
+    let data = SixtyFourGameInstruction::ResolveAttack { from_square, secret }.pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(*auction_info, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(sysvar::slot_hashes::id(), false),
+        AccountMeta::new(*active_players_list, false),
+        AccountMeta::new(*all_game_squares_list, false),
+        AccountMeta::new_readonly(auction_config_address(program_id), false),
+        AccountMeta::new(game_state_address(program_id), false),
+    ];
+    Instruction { program_id: *program_id, accounts, data }
+}
+
+/// Creates a ClaimPrize instruction, claimer_square_token_account is the claimer's token
+/// account holding the square's NFT, only passed when the square is not in play
+pub fn claim_prize(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    claimer: &Pubkey,
+    square: u64,
+    game_accounts: &GameAccounts,
+    claimer_square_token_account: Option<&Pubkey>,
+) -> Instruction {
+    let data = SixtyFourGameInstruction::ClaimPrize { square }.pack();
+    let mut accounts = vec![
+        AccountMeta::new(*payer, true),
+        AccountMeta::new(*claimer, false),
+        AccountMeta::new_readonly(game_accounts.auction_info, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(sysvar::slot_hashes::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(game_accounts.active_players_list, false),
+        AccountMeta::new(game_accounts.all_game_squares_list, false),
+        AccountMeta::new(treasury_address(program_id), false),
+        AccountMeta::new(game_state_address(program_id), false),
+        AccountMeta::new_readonly(auction_config_address(program_id), false),
+    ];
+    if let Some(claimer_square_token_account) = claimer_square_token_account {
+        accounts.push(AccountMeta::new_readonly(*claimer_square_token_account, false));
+    }
+    Instruction { program_id: *program_id, accounts, data }
+}
+
+/// Creates a ClaimRefund instruction
+pub fn claim_refund(
+    program_id: &Pubkey,
+    bidder: &Pubkey,
+    bid_number: u64,
+    auction_info: &Pubkey,
+) -> Instruction {
+    let data = SixtyFourGameInstruction::ClaimRefund { bid_number }.pack();
+    let accounts = vec![
+        AccountMeta::new(*bidder, false),
+        AccountMeta::new(auction_list_address(program_id, bid_number), false),
+        AccountMeta::new(treasury_address(program_id), false),
+        AccountMeta::new(*auction_info, false),
+        AccountMeta::new_readonly(auction_config_address(program_id), false),
+        AccountMeta::new_readonly(game_state_address(program_id), false),
+    ];
+    Instruction { program_id: *program_id, accounts, data }
+}
+
+/// Creates a SetAdmin instruction
+pub fn set_admin(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    new_admin: &Pubkey,
+) -> Instruction {
+    let data = SixtyFourGameInstruction::SetAdmin { new_admin: *new_admin }.pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(*admin, true),
+        AccountMeta::new(auction_config_address(program_id), false),
+    ];
+    Instruction { program_id: *program_id, accounts, data }
+}
+
+/// Creates a RenounceAdmin instruction
+pub fn renounce_admin(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+) -> Instruction {
+    let data = SixtyFourGameInstruction::RenounceAdmin {}.pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(*admin, true),
+        AccountMeta::new(auction_config_address(program_id), false),
+    ];
+    Instruction { program_id: *program_id, accounts, data }
+}
+
+/// Creates a PurgeInactive instruction
+pub fn purge_inactive(
+    program_id: &Pubkey,
+    square: u64,
+    auction_info: &Pubkey,
+    active_players_list: &Pubkey,
+    all_game_squares_list: &Pubkey,
+) -> Instruction {
+    let data = SixtyFourGameInstruction::PurgeInactive { square }.pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(*auction_info, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(auction_config_address(program_id), false),
+        AccountMeta::new(game_state_address(program_id), false),
+        AccountMeta::new_readonly(*active_players_list, false),
+        AccountMeta::new(*all_game_squares_list, false),
+    ];
+    Instruction { program_id: *program_id, accounts, data }
+}
+
+/// Creates an IncreaseBid instruction
+pub fn increase_bid(
+    program_id: &Pubkey,
+    bidder: &Pubkey,
+    bid_number: u64,
+    additional_lamports: u64,
+    auction_info: &Pubkey,
+    bid_index: &Pubkey,
+) -> Instruction {
+    let data = SixtyFourGameInstruction::IncreaseBid { bid_number, additional_lamports }.pack();
+    let accounts = vec![
+        AccountMeta::new(*bidder, true),
+        AccountMeta::new(auction_list_address(program_id, bid_number), false),
+        AccountMeta::new(treasury_address(program_id), false),
+        AccountMeta::new(*auction_info, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(auction_config_address(program_id), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(game_state_address(program_id), false),
+        AccountMeta::new(*bid_index, false),
+    ];
+    Instruction { program_id: *program_id, accounts, data }
+}
+
+/// Creates a CommitBid instruction, bid_number is the AuctionInfo bid_count the bid is saved at
+/// and commitment is util::bid_commitment of the amount and salt revealed later
+pub fn commit_bid(
+    program_id: &Pubkey,
+    bidder: &Pubkey,
+    deposit: u64,
+    commitment: Hash,
+    bid_number: u64,
+    auction_info: &Pubkey,
+) -> Instruction {
+    let data = SixtyFourGameInstruction::CommitBid { deposit, commitment }.pack();
+    let accounts = vec![
+        AccountMeta::new(*bidder, true),
+        AccountMeta::new(auction_list_address(program_id, bid_number), false),
+        AccountMeta::new(treasury_address(program_id), false),
+        AccountMeta::new(*auction_info, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(auction_config_address(program_id), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(game_state_address(program_id), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ];
+    Instruction { program_id: *program_id, accounts, data }
+}
+
+/// Creates a RevealBid instruction
+pub fn reveal_bid(
+    program_id: &Pubkey,
+    bidder: &Pubkey,
+    bid_number: u64,
+    amount: u64,
+    salt: [u8; 32],
+    auction_info: &Pubkey,
+    bid_index: &Pubkey,
+) -> Instruction {
+    let data = SixtyFourGameInstruction::RevealBid { bid_number, amount, salt }.pack();
+    let accounts = vec![
+        AccountMeta::new(*bidder, true),
+        AccountMeta::new(auction_list_address(program_id, bid_number), false),
+        AccountMeta::new(treasury_address(program_id), false),
+        AccountMeta::new(*auction_info, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(auction_config_address(program_id), false),
+        AccountMeta::new_readonly(game_state_address(program_id), false),
+        AccountMeta::new(*bid_index, false),
+    ];
+    Instruction { program_id: *program_id, accounts, data }
+}
+
+/// Creates a BuySquare instruction, mint and token_account must already be allocated
+pub fn buy_square(
+    program_id: &Pubkey,
+    buyer: &Pubkey,
+    max_price: u64,
+    auction_info: &Pubkey,
+    mint: &Pubkey,
+    token_account: &Pubkey,
+    all_game_squares_list: &Pubkey,
+) -> Instruction {
+    let data = SixtyFourGameInstruction::BuySquare { max_price }.pack();
+    let (mint_pda, _) = Pubkey::find_program_address(&[b"mint"], program_id);
+    let accounts = vec![
+        AccountMeta::new(*buyer, true),
+        AccountMeta::new(*auction_info, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new(*mint, false),
+        AccountMeta::new(*token_account, false),
+        AccountMeta::new_readonly(mint_pda, false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(*all_game_squares_list, false),
+        AccountMeta::new(treasury_address(program_id), false),
+        AccountMeta::new_readonly(auction_config_address(program_id), false),
+        AccountMeta::new(game_state_address(program_id), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    Instruction { program_id: *program_id, accounts, data }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_pack_unpack() {
+        let instructions = vec![
+            SixtyFourGameInstruction::InititateAuction {
+                params: InitiateAuctionParams {
+                    auction_end_slot: 1,
+                    max_bid_count: 1000,
+                    board_side: 8,
+                    team_count: 4,
+                    starting_health: 100000000,
+                    showdown_delay_slots: 6000000,
+                    soft_close_slots: 150,
+                    extension_slots: 75,
+                    max_extension_slots: 9000,
+                    min_bid_lamports: 10,
+                    reserve_price_lamports: 1000,
+                    reserve_step_lamports: 10,
+                    min_increment_lamports: 5,
+                    top_up_keeps_priority: true,
+                    reveal_slots: 300,
+                    forfeit_unrevealed: true,
+                    auction_mode: AuctionMode::Dutch,
+                    dutch_start_price_lamports: 5000,
+                    dutch_price_step_lamports: 20,
+                    bid_mint: Pubkey::new_unique(),
+                },
+            },
+            SixtyFourGameInstruction::Bid { amount: 42 },
+            SixtyFourGameInstruction::CancelBid { bid_number: 7 },
+            SixtyFourGameInstruction::MintNFT {},
+            SixtyFourGameInstruction::InitiatePlay { square: 3 },
+            SixtyFourGameInstruction::EndPlay { square: 3 },
+            SixtyFourGameInstruction::Attack {
+                amount: 5,
+                from_square: 0,
+                to_square: 1,
+                commitment: Hash::new_from_array([3; 32]),
+            },
+            SixtyFourGameInstruction::ClaimPrize { square: 63 },
+            SixtyFourGameInstruction::ClaimRefund { bid_number: 9 },
+            SixtyFourGameInstruction::SetAdmin { new_admin: Pubkey::new_unique() },
+            SixtyFourGameInstruction::RenounceAdmin {},
+            SixtyFourGameInstruction::PurgeInactive { square: 12 },
+            SixtyFourGameInstruction::ResolveAttack { from_square: 0, secret: [4; 32] },
+            SixtyFourGameInstruction::IncreaseBid { bid_number: 7, additional_lamports: 500 },
+            SixtyFourGameInstruction::CommitBid { deposit: 900, commitment: Hash::new_from_array([5; 32]) },
+            SixtyFourGameInstruction::RevealBid { bid_number: 7, amount: 600, salt: [6; 32] },
+            SixtyFourGameInstruction::BuySquare { max_price: 4000 },
+        ];
+        for instruction in instructions {
+            let packed = instruction.pack();
+            assert_eq!(SixtyFourGameInstruction::unpack(&packed).unwrap(), instruction);
+        }
+    }
+
+    #[test]
+    fn test_bid_accounts() {
+        let program_id = Pubkey::new_unique();
+        let bidder = Pubkey::new_unique();
+        let instruction = bid(&program_id, &bidder, 42, 70, &Pubkey::new_unique(), &Pubkey::new_unique());
+        assert_eq!(instruction.data, vec![1, 42, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(instruction.accounts[0], AccountMeta::new(bidder, true));
+        assert_eq!(instruction.accounts[1].pubkey, auction_list_address(&program_id, 64));
+        assert_ne!(auction_list_address(&program_id, 63), auction_list_address(&program_id, 64));
+        assert_eq!(instruction.accounts[2].pubkey, treasury_address(&program_id));
+    }
+
+    #[test]
+    fn test_bid_token_accounts() {
+        let program_id = Pubkey::new_unique();
+        let token_account = Pubkey::new_unique();
+        let instruction = cancel_bid(&program_id, &Pubkey::new_unique(), 3, &Pubkey::new_unique(), &Pubkey::new_unique());
+        let account_count = instruction.accounts.len();
+        let instruction = with_bid_token_accounts(&program_id, instruction, &token_account);
+        assert_eq!(instruction.accounts.len(), account_count + 4);
+        assert_eq!(instruction.accounts[account_count], AccountMeta::new(token_account, false));
+        assert_eq!(instruction.accounts[account_count + 1], AccountMeta::new(bid_escrow_address(&program_id), false));
+        assert_eq!(instruction.accounts[account_count + 3].pubkey, spl_token::id());
+    }
+
+    #[test]
+    fn test_initiate_play_accounts() {
+        let program_id = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let game_accounts = GameAccounts {
+            auction_info: Pubkey::new_unique(),
+            bid_index: Pubkey::new_unique(),
+            all_game_squares_list: Pubkey::new_unique(),
+            active_players_list: Pubkey::new_unique(),
+        };
+        let instruction = initiate_play(&program_id, &Pubkey::new_unique(), 5, &Pubkey::new_unique(), &mint, &game_accounts);
+        assert_eq!(instruction.accounts[2].pubkey, game_accounts.auction_info);
+        assert_eq!(instruction.accounts[6].pubkey, program_token_address(&program_id, 5, &mint));
+        assert_eq!(instruction.accounts[9], AccountMeta::new(game_accounts.active_players_list, false));
+        assert_ne!(program_token_address(&program_id, 5, &mint), program_token_address(&program_id, 6, &mint));
     }
 }
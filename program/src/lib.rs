@@ -1,8 +1,13 @@
 pub mod util;
+pub mod bid_index;
+pub mod board;
 pub mod error;
 pub mod instruction;
 pub mod processor;
 pub mod state;
 
+#[cfg(test)]
+mod test_runtime;
+
 #[cfg(not(feature = "no-entrypoint"))]
 pub mod entrypoint;
@@ -4,16 +4,17 @@ use solana_sdk::{
     account_info::{next_account_info, AccountInfo},
     entrypoint,
     entrypoint::ProgramResult,
+    hash::Hash,
     msg,
     program_error::ProgramError,
     pubkey::Pubkey,
     system_instruction,
     sysvar::{
-        rent::Rent, clock::Clock, slot_hashes::SlotHashes, Sysvar,
+        self, rent::Rent, clock::Clock, slot_hashes::SlotHashes, Sysvar,
     },
     program_option::COption,
 };
-use solana_sdk::program::invoke_signed;
+use solana_sdk::program::{invoke, invoke_signed};
 // use spl_token::{instruction};
 use solana_sdk::program_pack::Pack as TokenPack;
 use spl_token::state::{Account as TokenAccount, Mint};
@@ -24,14 +25,37 @@ use solana_sdk::{decode_error::DecodeError};
 use thiserror::Error;
 
 use crate::{
+    bid_index,
+    board,
     error::SixtyFourGameError,
-    instruction::SixtyFourGameInstruction,
-    state::{BidEntry, AuctionInfo, GameSquare, ActivePlayer},
-    util::{hash_value, get_slot_hash, unpack_mint},
+    instruction::{InitiateAuctionParams, SixtyFourGameInstruction},
+    state::{
+        BidEntry, BidStatus, AuctionConfig, AuctionInfo, AuctionMode, GameSquare, GamePhase, GameState, ActivePlayer,
+        ActivePlayerStatus, MAX_TEAM_COUNT,
+    },
+    util::{
+        attack_commitment, attack_roll, bid_commitment, find_slot_hash, unpack_mint, check_auction_config_account, check_treasury_account,
+        check_game_state_account, check_program_token_account, check_program_token_owner, check_bid_index_account,
+        check_auction_list_page_account, auction_list_page, auction_list_offset, check_bid_escrow_account,
+        check_all_game_squares_list_account, check_auction_info_account, check_active_players_list_account,
+        check_square_holder,
+        create_program_address_account,
+        AUCTION_CONFIG_SEED, TREASURY_SEED, GAME_STATE_SEED, AUCTION_LIST_SEED, BIDS_PER_AUCTION_LIST_PAGE, PROGRAM_TOKEN_ACCOUNT_SEED, PROGRAM_TOKEN_OWNER_SEED,
+        BID_ESCROW_SEED,
+        ATTACK_SLOT_DELAY, ATTACK_REVEAL_WINDOW,
+    },
 };
 
-const GAME_OVER_TEAM_NUMBER_UNCLAIMED: u64 = 99;
-const GAME_OVER_TEAM_NUMBER_CLAIMED: u64 = 100;
+
+/// Accounts minting a game square NFT to its owner, see instruction::mint_nft
+struct SquareMintAccounts<'a, 'b> {
+    owner: &'b AccountInfo<'a>,
+    mint: &'b AccountInfo<'a>,
+    token_account: &'b AccountInfo<'a>,
+    mint_pda: &'b AccountInfo<'a>,
+    rent: &'b AccountInfo<'a>,
+    spl_token_program: &'b AccountInfo<'a>,
+}
 
 pub struct Processor;
 impl Processor {
@@ -43,17 +67,33 @@ impl Processor {
         let instruction = SixtyFourGameInstruction::unpack(instruction_data)?;
 
         match instruction {
-            SixtyFourGameInstruction::InititateAuction { auction_end_slot } => {
+            SixtyFourGameInstruction::InititateAuction { params } => {
                 msg!("SixtyFourGameInstruction: InititateAuction");
-                Self::process_initiate_auction(accounts, auction_end_slot, program_id)
+                Self::process_initiate_auction(accounts, params, program_id)
             }
             SixtyFourGameInstruction::Bid { amount } => {
                 msg!("SixtyFourGameInstruction: Bid");
                 Self::process_bid(accounts, amount, program_id)
             }
-            SixtyFourGameInstruction::CancelBid { } => {
+            SixtyFourGameInstruction::CancelBid { bid_number } => {
                 msg!("SixtyFourGameInstruction: CancelBid");
-                Self::process_cancel_bid(accounts, program_id)
+                Self::process_cancel_bid(accounts, bid_number, program_id)
+            }
+            SixtyFourGameInstruction::IncreaseBid { bid_number, additional_lamports } => {
+                msg!("SixtyFourGameInstruction: IncreaseBid");
+                Self::process_increase_bid(accounts, bid_number, additional_lamports, program_id)
+            }
+            SixtyFourGameInstruction::CommitBid { deposit, commitment } => {
+                msg!("SixtyFourGameInstruction: CommitBid");
+                Self::process_commit_bid(accounts, deposit, commitment, program_id)
+            }
+            SixtyFourGameInstruction::RevealBid { bid_number, amount, salt } => {
+                msg!("SixtyFourGameInstruction: RevealBid");
+                Self::process_reveal_bid(accounts, bid_number, amount, salt, program_id)
+            }
+            SixtyFourGameInstruction::BuySquare { max_price } => {
+                msg!("SixtyFourGameInstruction: BuySquare");
+                Self::process_buy_square(accounts, max_price, program_id)
             }
             SixtyFourGameInstruction::MintNFT {  } => {
                 msg!("SixtyFourGameInstruction: MintNFT");
@@ -67,24 +107,44 @@ impl Processor {
                 msg!("SixtyFourGameInstruction: EndPlay");
                 Self::process_end_play(accounts, square, program_id)
             }
-            SixtyFourGameInstruction::Attack { amount, from_square, to_square } => {
+            SixtyFourGameInstruction::Attack { amount, from_square, to_square, commitment } => {
                 msg!("SixtyFourGameInstruction: Attack");
-                Self::process_attack(accounts, amount, from_square, to_square, program_id)
+                Self::process_attack(accounts, amount, from_square, to_square, commitment, program_id)
             }
             SixtyFourGameInstruction::ClaimPrize { square } => {
                 msg!("SixtyFourGameInstruction: ClaimPrize");
                 Self::process_claim_prize(accounts, square, program_id)
             }
+            SixtyFourGameInstruction::ClaimRefund { bid_number } => {
+                msg!("SixtyFourGameInstruction: ClaimRefund");
+                Self::process_claim_refund(accounts, bid_number, program_id)
+            }
+            SixtyFourGameInstruction::SetAdmin { new_admin } => {
+                msg!("SixtyFourGameInstruction: SetAdmin");
+                Self::process_set_admin(accounts, new_admin, program_id)
+            }
+            SixtyFourGameInstruction::RenounceAdmin {} => {
+                msg!("SixtyFourGameInstruction: RenounceAdmin");
+                Self::process_set_admin(accounts, Pubkey::default(), program_id)
+            }
+            SixtyFourGameInstruction::PurgeInactive { square } => {
+                msg!("SixtyFourGameInstruction: PurgeInactive");
+                Self::process_purge_inactive(accounts, square, program_id)
+            }
+            SixtyFourGameInstruction::ResolveAttack { from_square, secret } => {
+                msg!("SixtyFourGameInstruction: ResolveAttack");
+                Self::process_resolve_attack(accounts, from_square, secret, program_id)
+            }
         }
     }
 
     pub fn process_initiate_auction(
         accounts: &[AccountInfo],
-        auction_end_slot: u64,
+        params: InitiateAuctionParams,
         program_id: &Pubkey,
     ) -> ProgramResult {
 
-        let sauction_end_slot = auction_end_slot.to_string();
+        let sauction_end_slot = params.auction_end_slot.to_string();
         let auction_end_slot_str: &str = &sauction_end_slot;
         msg!("Setting Auction End Slot to:");
         msg!(auction_end_slot_str);
@@ -93,29 +153,240 @@ impl Processor {
         let accounts_iter = &mut accounts.iter();
         let admin_account = next_account_info(accounts_iter)?;
         let auction_info_account = next_account_info(accounts_iter)?;
+        let auction_config_account = next_account_info(accounts_iter)?;
+        let treasury_account = next_account_info(accounts_iter)?;
+        let game_state_account = next_account_info(accounts_iter)?;
+        let rent_account = next_account_info(accounts_iter)?;
+        let system_program = next_account_info(accounts_iter)?;
+        let bid_index_account = next_account_info(accounts_iter)?;
+        let sysvar_account = next_account_info(accounts_iter)?;
+        let program_account = next_account_info(accounts_iter)?;
+        let all_game_squares_list_account = next_account_info(accounts_iter)?;
+        let active_players_list_account = next_account_info(accounts_iter)?;
 
-        // Save a BidEntry into the auction list account
-        let mut auction_info = AuctionInfo::unpack_unchecked(&auction_info_account.data.borrow())?;
+        // Confirm admin is signer
+        if !admin_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if *auction_info_account.owner != *program_id {
+            msg!("Auction info account is not owned by the program");
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if *bid_index_account.owner != *program_id {
+            msg!("Bid index account is not owned by the program");
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        // Create the AuctionConfig program address on first use
+        let rent = Rent::from_account_info(rent_account)?;
+        let config_bump_seed = check_auction_config_account(program_id, auction_config_account)?;
+        create_program_address_account(
+            admin_account,
+            auction_config_account,
+            system_program,
+            &rent,
+            AuctionConfig::LEN,
+            &[AUCTION_CONFIG_SEED, &[config_bump_seed]],
+            program_id,
+        )?;
+
+        // First initializer becomes the admin, afterwards admin only. Only the holder of the
+        // program keypair can co-sign the first call, so the admin cannot be front-run
+        let mut auction_config = AuctionConfig::unpack_unchecked(&auction_config_account.data.borrow())?;
+        if !auction_config.is_initialized {
+            if *program_account.key != *program_id || !program_account.is_signer {
+                msg!("First InitiateAuction has to be signed by the program account");
+                return Err(SixtyFourGameError::NotAdmin.into());
+            }
+            auction_config.is_initialized = true;
+            auction_config.admin_pubkey = *admin_account.key;
+        } else if auction_config.admin_pubkey != *admin_account.key {
+            msg!("Only the admin can initiate the auction");
+            return Err(SixtyFourGameError::NotAdmin.into());
+        }
 
-        // TODO; admin only
-        if auction_info.auction_enabled {
+        // Create the program owned treasury, holds no data
+        let treasury_bump_seed = check_treasury_account(program_id, treasury_account)?;
+        create_program_address_account(
+            admin_account,
+            treasury_account,
+            system_program,
+            &rent,
+            0,
+            &[TREASURY_SEED, &[treasury_bump_seed]],
+            program_id,
+        )?;
+
+        // Create the GameState, the auction can only be started once
+        let game_state_bump_seed = check_game_state_account(program_id, game_state_account)?;
+        create_program_address_account(
+            admin_account,
+            game_state_account,
+            system_program,
+            &rent,
+            GameState::LEN,
+            &[GAME_STATE_SEED, &[game_state_bump_seed]],
+            program_id,
+        )?;
+        let mut game_state = GameState::unpack_unchecked(&game_state_account.data.borrow())?;
+        if game_state.game_phase != GamePhase::Uninitialized {
             msg!("Auction already started");
-            return Err(ProgramError::InvalidAccountData);
+            return Err(SixtyFourGameError::AuctionAlreadyStarted.into());
+        }
+        game_state.game_phase = GamePhase::Auction;
+
+        auction_config.max_bid_count = params.max_bid_count;
+        auction_config.board_side = params.board_side;
+        auction_config.team_count = params.team_count;
+        auction_config.starting_health = params.starting_health;
+        auction_config.showdown_delay_slots = params.showdown_delay_slots;
+        auction_config.soft_close_slots = params.soft_close_slots;
+        auction_config.extension_slots = params.extension_slots;
+        auction_config.max_auction_end_slot = params.auction_end_slot.saturating_add(params.max_extension_slots);
+        auction_config.min_bid_lamports = params.min_bid_lamports;
+        auction_config.reserve_price_lamports = params.reserve_price_lamports;
+        auction_config.reserve_step_lamports = params.reserve_step_lamports;
+        auction_config.min_increment_lamports = params.min_increment_lamports;
+        auction_config.top_up_keeps_priority = params.top_up_keeps_priority;
+        auction_config.reveal_slots = params.reveal_slots;
+        auction_config.forfeit_unrevealed = params.forfeit_unrevealed;
+        auction_config.auction_mode = params.auction_mode;
+        auction_config.dutch_start_price_lamports = params.dutch_start_price_lamports;
+        auction_config.dutch_price_step_lamports = params.dutch_price_step_lamports;
+        auction_config.bid_mint_pubkey = params.bid_mint;
+        if params.board_side < 2 ||
+            params.board_side > board::MAX_BOARD_SIDE ||
+            params.team_count < 2 ||
+            params.team_count > MAX_TEAM_COUNT ||
+            params.team_count > auction_config.max_game_square_count() ||
+            params.max_bid_count < auction_config.max_game_square_count() ||
+            params.min_bid_lamports == 0 ||
+            params.starting_health == 0 ||
+            (params.auction_mode == AuctionMode::Dutch && (params.reveal_slots > 0 || params.dutch_start_price_lamports < params.min_bid_lamports)) {
+            msg!("Invalid auction parameters");
+            return Err(SixtyFourGameError::InvalidAuctionParameters.into());
+        }
+
+        // Bid index needs room for every bid
+        if bid_index_account.data_len() < bid_index::bid_index_space(params.max_bid_count) {
+            msg!("Bid index account is too small for max_bid_count");
+            return Err(SixtyFourGameError::InvalidAuctionParameters.into());
+        }
+        bid_index::clear(&mut bid_index_account.data.borrow_mut());
+        auction_config.bid_index_pubkey = *bid_index_account.key;
+
+        // Game squares list needs room for every square
+        if *all_game_squares_list_account.owner != *program_id {
+            msg!("Game squares list account is not owned by the program");
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if all_game_squares_list_account.data_len() < auction_config.max_game_square_count() as usize * GameSquare::LEN {
+            msg!("Game squares list account is too small for the board");
+            return Err(SixtyFourGameError::InvalidAuctionParameters.into());
+        }
+        auction_config.all_game_squares_list_pubkey = *all_game_squares_list_account.key;
+
+        // Active players list needs room for every square
+        if *active_players_list_account.owner != *program_id {
+            msg!("Active players list account is not owned by the program");
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if active_players_list_account.data_len() < auction_config.max_game_square_count() as usize * ActivePlayer::LEN {
+            msg!("Active players list account is too small for the board");
+            return Err(SixtyFourGameError::InvalidAuctionParameters.into());
+        }
+        auction_config.active_players_list_pubkey = *active_players_list_account.key;
+        auction_config.auction_info_pubkey = *auction_info_account.key;
+
+        // Bids in a token are escrowed in a program token account of the bid mint
+        if auction_config.is_token_bid() {
+            let bid_mint_account = next_account_info(accounts_iter)?;
+            let bid_escrow_account = next_account_info(accounts_iter)?;
+            let program_token_pda_account = next_account_info(accounts_iter)?;
+            let spl_token_program = next_account_info(accounts_iter)?;
+            if *bid_mint_account.key != params.bid_mint {
+                msg!("Mint account does not match the bid mint");
+                return Err(SixtyFourGameError::InvalidAuctionParameters.into());
+            }
+            check_program_token_owner(program_id, program_token_pda_account)?;
+            let escrow_bump_seed = check_bid_escrow_account(program_id, bid_escrow_account)?;
+            create_program_address_account(
+                admin_account,
+                bid_escrow_account,
+                system_program,
+                &rent,
+                TokenAccount::LEN,
+                &[BID_ESCROW_SEED, &[escrow_bump_seed]],
+                &spl_token::id(),
+            )?;
+            let init_account_instr = spl_token::instruction::initialize_account(
+                &spl_token::ID,
+                bid_escrow_account.key,
+                bid_mint_account.key,
+                program_token_pda_account.key,
+            )?;
+            invoke(
+                &init_account_instr,
+                &[
+                    bid_escrow_account.clone(),
+                    bid_mint_account.clone(),
+                    program_token_pda_account.clone(),
+                    rent_account.clone(),
+                    spl_token_program.clone(),
+                ],
+            )?;
         }
 
+        let mut auction_info = AuctionInfo::unpack_unchecked(&auction_info_account.data.borrow())?;
         auction_info.bid_count = 0;
         auction_info.squares_minted = 0;
-        auction_info.auction_end_slot = auction_end_slot;
-        auction_info.auction_enabled = true;
+        auction_info.auction_end_slot = params.auction_end_slot;
+        auction_info.page_count = 0;
+        auction_info.bid_sequence = 0;
+        auction_info.sealed_deposits = 0;
+        auction_info.price_start_slot = Clock::from_account_info(sysvar_account)?.slot;
 
         msg!("Saving auction end slot");
 
+        AuctionConfig::pack(auction_config, &mut auction_config_account.data.borrow_mut())?;
         AuctionInfo::pack(auction_info, &mut auction_info_account.data.borrow_mut())?;
+        GameState::pack(game_state, &mut game_state_account.data.borrow_mut())?;
 
         msg!("InitAuction successful");
         Ok(())
     }
 
+    pub fn process_set_admin(
+        accounts: &[AccountInfo],
+        new_admin: Pubkey,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+
+        // Set accounts
+        let accounts_iter = &mut accounts.iter();
+        let admin_account = next_account_info(accounts_iter)?;
+        let auction_config_account = next_account_info(accounts_iter)?;
+
+        // Confirm admin is signer
+        if !admin_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        check_auction_config_account(program_id, auction_config_account)?;
+        let mut auction_config = AuctionConfig::unpack(&auction_config_account.data.borrow())?;
+        if auction_config.admin_pubkey != *admin_account.key {
+            msg!("Only the admin can change the admin");
+            return Err(SixtyFourGameError::NotAdmin.into());
+        }
+
+        // Default pubkey cannot sign, setting it renounces the admin role
+        auction_config.admin_pubkey = new_admin;
+        AuctionConfig::pack(auction_config, &mut auction_config_account.data.borrow_mut())?;
+
+        msg!("Set admin successful");
+        Ok(())
+    }
+
     pub fn process_bid(
         accounts: &[AccountInfo],
         amount: u64,
@@ -126,37 +397,95 @@ impl Processor {
         let accounts_iter = &mut accounts.iter();
         let bidder_account = next_account_info(accounts_iter)?;
         let auction_list_account = next_account_info(accounts_iter)?;
-        let treasury_fund_account = next_account_info(accounts_iter)?;
         let treasury_account = next_account_info(accounts_iter)?;
         let auction_info_account = next_account_info(accounts_iter)?;
         let sysvar_account = next_account_info(accounts_iter)?;
+        let auction_config_account = next_account_info(accounts_iter)?;
+        let system_program = next_account_info(accounts_iter)?;
+        let game_state_account = next_account_info(accounts_iter)?;
+        let bid_index_account = next_account_info(accounts_iter)?;
+        let rent_account = next_account_info(accounts_iter)?;
+
+        // Confirm bidder is signer
+        if !bidder_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        check_treasury_account(program_id, treasury_account)?;
+        check_auction_config_account(program_id, auction_config_account)?;
+        let auction_config = AuctionConfig::unpack(&auction_config_account.data.borrow())?;
+        check_auction_info_account(program_id, &auction_config, auction_info_account)?;
+        check_bid_index_account(&auction_config, bid_index_account)?;
+        check_game_state_account(program_id, game_state_account)?;
+        let mut game_state = GameState::unpack_unchecked(&game_state_account.data.borrow())?;
 
         // Dont allow bidding if after auction_end_slot
-        let max_bid_count = 1000;
         let current_slot = Clock::from_account_info(sysvar_account)?.slot;
         let mut auction_info = AuctionInfo::unpack_unchecked(&auction_info_account.data.borrow())?;
-        if !auction_info.auction_enabled ||
-            auction_info.auction_end_slot < current_slot ||
-            auction_info.bid_count >= max_bid_count ||
-            auction_info.squares_minted > 0 {
-            msg!("Auction is not active or bids have reached capacity");
-            return Err(ProgramError::InvalidAccountData); // TODO
+        Self::update_game_phase(&mut game_state, &auction_info, &auction_config, current_slot);
+        if game_state.game_phase != GamePhase::Auction {
+            msg!("Auction is not active");
+            return Err(SixtyFourGameError::AuctionNotActive.into());
+        }
+        if auction_config.auction_mode == AuctionMode::Dutch {
+            msg!("Dutch auction, use BuySquare");
+            return Err(SixtyFourGameError::InvalidBidMode.into());
+        }
+        if auction_config.is_sealed_bid() {
+            msg!("Auction takes sealed bids, use CommitBid");
+            return Err(SixtyFourGameError::InvalidBidMode.into());
+        }
+        if auction_info.bid_count >= auction_config.max_bid_count {
+            msg!("Bids have reached capacity");
+            return Err(SixtyFourGameError::AuctionFull.into());
         }
 
-        // Trasnfer bid amount to treasury - can refund if no nft given
-        **treasury_account.lamports.borrow_mut() += amount;
-        **treasury_fund_account.lamports.borrow_mut() -= amount;
+        // Reject bids that could never win a square
+        let priority = auction_info.bid_sequence;
+        Self::check_bid_amount(&auction_config, &bid_index_account.data.borrow(), amount, priority, false)?;
+
+        // Trasnfer bid amount to treasury or the bid escrow - can refund if no nft given
+        Self::collect_bid_funds(
+            &auction_config,
+            amount,
+            bidder_account,
+            treasury_account,
+            system_program,
+            accounts_iter,
+            program_id,
+        )?;
+
+        Self::open_auction_list_page(
+            &mut auction_info,
+            bidder_account,
+            auction_list_account,
+            system_program,
+            rent_account,
+            program_id,
+        )?;
 
-        // Save a BidEntry into the auction list account
-        let offset = (auction_info.bid_count * 48) as usize;
-        let mut auction_list_info = BidEntry::unpack_unchecked(&auction_list_account.data.borrow()[offset..(offset + 48)])?;
+        // Save a BidEntry into the auction list page
+        let offset = auction_list_offset(auction_info.bid_count);
+        let mut auction_list_info = BidEntry::unpack_unchecked(&auction_list_account.data.borrow()[offset..(offset + BidEntry::LEN)])?;
         auction_list_info.bid_number = auction_info.bid_count;
         auction_list_info.amount_lamports = amount;
         auction_list_info.bidder_pubkey = *bidder_account.key;
-        BidEntry::pack(auction_list_info, &mut auction_list_account.data.borrow_mut()[offset..(offset + 48)])?;
+        auction_list_info.bid_status = BidStatus::Active;
+        auction_list_info.priority = priority;
+        BidEntry::pack(auction_list_info, &mut auction_list_account.data.borrow_mut()[offset..(offset + BidEntry::LEN)])?;
+
+        // Keep the bid index in rank order
+        let rank = bid_index::insert(&mut bid_index_account.data.borrow_mut(), amount, priority, auction_info.bid_count)?;
+        let rank_string: String = rank.to_string();
+        let rank_str: &str = &rank_string;
+        msg!("Bid ranked:");
+        msg!(rank_str);
+
+        Self::apply_soft_close(&mut auction_info, &auction_config, rank, current_slot);
 
         // Increment bid counter
-        auction_info.bid_count = auction_info.bid_count + 1;
+        auction_info.bid_count += 1;
+        auction_info.bid_sequence += 1;
         AuctionInfo::pack(auction_info, &mut auction_info_account.data.borrow_mut())?;
 
         msg!("Bid successful");
@@ -165,306 +494,846 @@ impl Processor {
 
     pub fn process_cancel_bid(
         accounts: &[AccountInfo],
+        bid_number: u64,
         program_id: &Pubkey,
     ) -> ProgramResult {
 
-        msg!("Cancel Bid successful");
+        // Set accounts
+        let accounts_iter = &mut accounts.iter();
+        let bidder_account = next_account_info(accounts_iter)?;
+        let auction_list_account = next_account_info(accounts_iter)?;
+        let treasury_account = next_account_info(accounts_iter)?;
+        let auction_info_account = next_account_info(accounts_iter)?;
+        let sysvar_account = next_account_info(accounts_iter)?;
+        let auction_config_account = next_account_info(accounts_iter)?;
+        let game_state_account = next_account_info(accounts_iter)?;
+        let bid_index_account = next_account_info(accounts_iter)?;
+
+        // Confirm bidder is signer
+        if !bidder_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        check_treasury_account(program_id, treasury_account)?;
+        check_auction_config_account(program_id, auction_config_account)?;
+        let auction_config = AuctionConfig::unpack(&auction_config_account.data.borrow())?;
+        check_auction_info_account(program_id, &auction_config, auction_info_account)?;
+        check_bid_index_account(&auction_config, bid_index_account)?;
+        check_game_state_account(program_id, game_state_account)?;
+        let mut game_state = GameState::unpack_unchecked(&game_state_account.data.borrow())?;
+
+        // Dont allow cancelling if after auction_end_slot
+        let current_slot = Clock::from_account_info(sysvar_account)?.slot;
+        let mut auction_info = AuctionInfo::unpack_unchecked(&auction_info_account.data.borrow())?;
+        Self::update_game_phase(&mut game_state, &auction_info, &auction_config, current_slot);
+        if game_state.game_phase != GamePhase::Auction {
+            msg!("Auction is not active, cannot cancel bid");
+            return Err(SixtyFourGameError::AuctionNotActive.into());
+        }
+        if bid_number >= auction_info.bid_count {
+            msg!("Bid number does not exist");
+            return Err(SixtyFourGameError::InvalidBidNumber.into());
+        }
+
+        // Confirm signer placed this bid and it has not been cancelled yet
+        check_auction_list_page_account(program_id, auction_list_page(bid_number), auction_list_account)?;
+        let offset = auction_list_offset(bid_number);
+        let mut auction_list_info = BidEntry::unpack_unchecked(&auction_list_account.data.borrow()[offset..(offset + BidEntry::LEN)])?;
+        if auction_list_info.bidder_pubkey != *bidder_account.key {
+            msg!("Trying to cancel a bid placed by a different bidder");
+            return Err(SixtyFourGameError::NotBidder.into());
+        }
+        if auction_list_info.bid_status != BidStatus::Active && auction_list_info.bid_status != BidStatus::Sealed {
+            msg!("Bid has already been cancelled");
+            return Err(SixtyFourGameError::BidNotActive.into());
+        }
+
+        // Refund bid amount from treasury
+        Self::pay_bid_funds(&auction_config, auction_list_info.amount_lamports, bidder_account, treasury_account, accounts_iter, program_id)?;
 
-        // TODO
+        // Drop the bid from the ranking, the bid slot stays tombstoned in the auction list.
+        // Sealed bids are only ranked once revealed
+        if auction_list_info.bid_status == BidStatus::Sealed {
+            auction_info.sealed_deposits -= auction_list_info.amount_lamports;
+            AuctionInfo::pack(auction_info, &mut auction_info_account.data.borrow_mut())?;
+        } else {
+            bid_index::remove(
+                &mut bid_index_account.data.borrow_mut(),
+                auction_list_info.amount_lamports,
+                auction_list_info.priority,
+                bid_number,
+            )?;
+        }
+        auction_list_info.bid_status = BidStatus::Cancelled;
+        BidEntry::pack(auction_list_info, &mut auction_list_account.data.borrow_mut()[offset..(offset + BidEntry::LEN)])?;
 
+        msg!("Cancel Bid successful");
         Ok(())
     }
 
-    pub fn process_mint_nft(
+    pub fn process_increase_bid(
         accounts: &[AccountInfo],
+        bid_number: u64,
+        additional_lamports: u64,
         program_id: &Pubkey,
     ) -> ProgramResult {
 
-        let accounts_iter = &mut accounts.iter();
         // Set accounts
-        let payer_account = next_account_info(accounts_iter)?;
-        let bid_entry_account = next_account_info(accounts_iter)?;
+        let accounts_iter = &mut accounts.iter();
+        let bidder_account = next_account_info(accounts_iter)?;
         let auction_list_account = next_account_info(accounts_iter)?;
+        let treasury_account = next_account_info(accounts_iter)?;
         let auction_info_account = next_account_info(accounts_iter)?;
         let sysvar_account = next_account_info(accounts_iter)?;
-        let mint_account = next_account_info(accounts_iter)?;
-        let token_account = next_account_info(accounts_iter)?;
-        let mint_pda_account = next_account_info(accounts_iter)?;
-        let rent_account = next_account_info(accounts_iter)?;
-        let spl_token_program = next_account_info(accounts_iter)?;
-        let all_game_squares_list_account = next_account_info(accounts_iter)?;
-        let treasury_account = next_account_info(accounts_iter)?;
+        let auction_config_account = next_account_info(accounts_iter)?;
+        let system_program = next_account_info(accounts_iter)?;
+        let game_state_account = next_account_info(accounts_iter)?;
+        let bid_index_account = next_account_info(accounts_iter)?;
 
-        // Dont allow minting if before auction_info
+        // Confirm bidder is signer
+        if !bidder_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        check_treasury_account(program_id, treasury_account)?;
+        check_auction_config_account(program_id, auction_config_account)?;
+        let auction_config = AuctionConfig::unpack(&auction_config_account.data.borrow())?;
+        check_auction_info_account(program_id, &auction_config, auction_info_account)?;
+        check_bid_index_account(&auction_config, bid_index_account)?;
+        check_game_state_account(program_id, game_state_account)?;
+        let mut game_state = GameState::unpack_unchecked(&game_state_account.data.borrow())?;
+
+        // Dont allow top ups if after auction_end_slot
         let current_slot = Clock::from_account_info(sysvar_account)?.slot;
         let mut auction_info = AuctionInfo::unpack_unchecked(&auction_info_account.data.borrow())?;
-        if !auction_info.auction_enabled ||
-            auction_info.auction_end_slot >= current_slot {
-            msg!("Auction is active, cannot mint");
-            return Err(ProgramError::InvalidAccountData);
+        Self::update_game_phase(&mut game_state, &auction_info, &auction_config, current_slot);
+        if game_state.game_phase != GamePhase::Auction {
+            msg!("Auction is not active, cannot increase bid");
+            return Err(SixtyFourGameError::AuctionNotActive.into());
+        }
+        if bid_number >= auction_info.bid_count {
+            msg!("Bid number does not exist");
+            return Err(SixtyFourGameError::InvalidBidNumber.into());
+        }
+        if additional_lamports == 0 {
+            msg!("Top up amount is zero");
+            return Err(SixtyFourGameError::BidNotIncreased.into());
         }
 
-        let max_game_square_count = 64;
-        if auction_info.squares_minted >= max_game_square_count {
-            for i in 0..auction_info.bid_count {
-                // Search for all bids from bid_entry_account
-                let offset = (i * 48) as usize;
-                let mut auction_list_info = BidEntry::unpack_unchecked(&auction_list_account.data.borrow()[offset..(offset + 48)])?;
-
-                // REFUND USER
-                if auction_list_info.bidder_pubkey == *bid_entry_account.key {
-                    **treasury_account.lamports.borrow_mut() -= auction_list_info.amount_lamports;
-                    **bid_entry_account.lamports.borrow_mut() += auction_list_info.amount_lamports;
+        // Confirm signer placed this bid and it is still active
+        check_auction_list_page_account(program_id, auction_list_page(bid_number), auction_list_account)?;
+        let offset = auction_list_offset(bid_number);
+        let mut auction_list_info = BidEntry::unpack_unchecked(&auction_list_account.data.borrow()[offset..(offset + BidEntry::LEN)])?;
+        if auction_list_info.bidder_pubkey != *bidder_account.key {
+            msg!("Trying to increase a bid placed by a different bidder");
+            return Err(SixtyFourGameError::NotBidder.into());
+        }
+        if auction_list_info.bid_status != BidStatus::Active {
+            msg!("Bid is not active");
+            return Err(SixtyFourGameError::BidNotActive.into());
+        }
 
-                    // Zero out lamports so no duplicates
-                    auction_list_info.amount_lamports = 0;
-                    BidEntry::pack(auction_list_info, &mut auction_list_account.data.borrow_mut()[offset..(offset + 48)])?;
-                }
-            }
-            msg!("Bid Refund successful");
+        // Take the bid out of the ranking, it is checked and ranked again at the new amount. A bid
+        // already in the winning ranks stays there, only bids entering them need the increment
+        let was_winning = bid_index::rank(
+            &bid_index_account.data.borrow(),
+            auction_list_info.amount_lamports,
+            auction_list_info.priority,
+        ) < auction_config.max_game_square_count();
+        bid_index::remove(
+            &mut bid_index_account.data.borrow_mut(),
+            auction_list_info.amount_lamports,
+            auction_list_info.priority,
+            bid_number,
+        )?;
+        let amount = auction_list_info.amount_lamports
+            .checked_add(additional_lamports)
+            .ok_or(SixtyFourGameError::BidAmountOverflow)?;
+        let priority = if auction_config.top_up_keeps_priority {
+            auction_list_info.priority
         } else {
+            auction_info.bid_sequence += 1;
+            auction_info.bid_sequence - 1
+        };
+        Self::check_bid_amount(&auction_config, &bid_index_account.data.borrow(), amount, priority, was_winning)?;
+
+        // Transfer the top up to the treasury
+        Self::collect_bid_funds(
+            &auction_config,
+            additional_lamports,
+            bidder_account,
+            treasury_account,
+            system_program,
+            accounts_iter,
+            program_id,
+        )?;
 
-            // Max bid count tested at 1000 here, used 15k/20k TODO: improve this
-            let mut highest_bid_amount_lamports = 0;
-            let mut highest_bid_bid_number = 0;
-            let mut highest_bidder_pubkey = *mint_pda_account.key; //placeholder, TODO:fix this
-            for i in 0..auction_info.bid_count {
-
-                // Fetch BidEntry to get highest
-                let offset = (i * 48) as usize;
-                let mut auction_list_info = BidEntry::unpack_unchecked(&auction_list_account.data.borrow()[offset..(offset + 48)])?;
-
-                if highest_bid_amount_lamports < auction_list_info.amount_lamports {
-                     highest_bid_amount_lamports = auction_list_info.amount_lamports;
-                     highest_bidder_pubkey = auction_list_info.bidder_pubkey;
-                     highest_bid_bid_number = auction_list_info.bid_number;
-                }
-            }
-
-            if highest_bidder_pubkey != *bid_entry_account.key {
-                msg!("Trying to MintNFT for account that is not the higest bidder");
-                return Err(ProgramError::InvalidAccountData);
-            }
-
-            // Inititalize mint - program
-            let mint_instr = spl_token::instruction::initialize_mint(
-                &spl_token::ID,
-                mint_account.key,
-                mint_pda_account.key,
-                Option::Some(mint_pda_account.key),
-                0
-            )?;
-            let account_infos = &[
-                mint_account.clone(),
-                spl_token_program.clone(),
-                rent_account.clone(),
-                mint_pda_account.clone()
-            ];
-            invoke_signed(
-                &mint_instr,
-                account_infos,
-                &[],
-            )?;
-
-            // Initialize token account
-            let init_account_instr = spl_token::instruction::initialize_account(
-                &spl_token::ID,
-                token_account.key,
-                mint_account.key,
-                bid_entry_account.key,
-            )?;
-            let init_account_account_infos = &[
-                token_account.clone(),
-                mint_account.clone(),
-                bid_entry_account.clone(),
-                rent_account.clone()
-            ];
-            invoke_signed(
-                &init_account_instr,
-                init_account_account_infos,
-                &[],
-            )?;
-
-            // Mint token to bidder
-            let (mint_address, mint_bump_seed) = Pubkey::find_program_address(&[b"mint"], &program_id);
-            let mint_to_instr = spl_token::instruction::mint_to(
-                &spl_token::ID,
-                mint_account.key,
-                token_account.key,
-                mint_pda_account.key,
-                &[],
-                1,
-            )?;
-            let account_infos = &[
-                mint_account.clone(),
-                token_account.clone(),
-                spl_token_program.clone(),
-                mint_pda_account.clone()
-            ];
-            let mint_signer_seeds: &[&[_]] = &[
-                b"mint",
-                &[mint_bump_seed],
-            ];
-            invoke_signed(
-                &mint_to_instr,
-                account_infos,
-                &[&mint_signer_seeds],
-            )?;
-
-            // Save a GameSquare into the all game squares list account
-            let mut offset = (auction_info.squares_minted * 56) as usize;
-            let mut all_game_squares_list_info = GameSquare::unpack_unchecked(&all_game_squares_list_account.data.borrow()[offset..(offset + 56)])?;
-            let game_square_number = auction_info.squares_minted;
-            all_game_squares_list_info.game_square_number = game_square_number;
-            all_game_squares_list_info.team_number = game_square_number % 4;
-            all_game_squares_list_info.health_number = 100000000;
-            all_game_squares_list_info.mint_pubkey = *mint_account.key;
-            GameSquare::pack(all_game_squares_list_info, &mut all_game_squares_list_account.data.borrow_mut()[offset..(offset + 56)])?;
-
-            // Increment squares minted - tracks current minting
-            auction_info.squares_minted += 1;
-            AuctionInfo::pack(auction_info, &mut auction_info_account.data.borrow_mut())?;
+        auction_list_info.amount_lamports = amount;
+        auction_list_info.priority = priority;
+        BidEntry::pack(auction_list_info, &mut auction_list_account.data.borrow_mut()[offset..(offset + BidEntry::LEN)])?;
 
-            // Prevent second mint
-            offset = (highest_bid_bid_number * 48) as usize;
-            let mut auction_list_info = BidEntry::unpack_unchecked(&auction_list_account.data.borrow()[offset..(offset + 48)])?;
-            auction_list_info.amount_lamports = 0;
-            BidEntry::pack(auction_list_info, &mut auction_list_account.data.borrow_mut()[offset..(offset + 48)])?;
+        let rank = bid_index::insert(&mut bid_index_account.data.borrow_mut(), amount, priority, bid_number)?;
+        let rank_string: String = rank.to_string();
+        let rank_str: &str = &rank_string;
+        msg!("Bid ranked:");
+        msg!(rank_str);
 
-            msg!("Mint NFT successful");
-        }
+        Self::apply_soft_close(&mut auction_info, &auction_config, rank, current_slot);
+        AuctionInfo::pack(auction_info, &mut auction_info_account.data.borrow_mut())?;
 
+        msg!("Increase Bid successful");
         Ok(())
     }
 
-    pub fn process_initiate_play(
+    pub fn process_commit_bid(
         accounts: &[AccountInfo],
-        square: u64,
+        deposit: u64,
+        commitment: Hash,
         program_id: &Pubkey,
     ) -> ProgramResult {
 
-        let accounts_iter = &mut accounts.iter();
         // Set accounts
-        let player_account = next_account_info(accounts_iter)?;
-        let game_square_token_account = next_account_info(accounts_iter)?;
+        let accounts_iter = &mut accounts.iter();
+        let bidder_account = next_account_info(accounts_iter)?;
+        let auction_list_account = next_account_info(accounts_iter)?;
+        let treasury_account = next_account_info(accounts_iter)?;
         let auction_info_account = next_account_info(accounts_iter)?;
         let sysvar_account = next_account_info(accounts_iter)?;
-        let mint_account = next_account_info(accounts_iter)?;
-        let program_token_pda_account = next_account_info(accounts_iter)?;
-        let program_token_account = next_account_info(accounts_iter)?;
+        let auction_config_account = next_account_info(accounts_iter)?;
+        let system_program = next_account_info(accounts_iter)?;
+        let game_state_account = next_account_info(accounts_iter)?;
         let rent_account = next_account_info(accounts_iter)?;
-        let spl_token_program = next_account_info(accounts_iter)?;
-        let active_players_list_account = next_account_info(accounts_iter)?;
-        let treasury_account = next_account_info(accounts_iter)?;
 
-        // Confirm player is signer
-        if !player_account.is_signer {
+        // Confirm bidder is signer
+        if !bidder_account.is_signer {
             return Err(ProgramError::MissingRequiredSignature);
         }
 
-        // Dont allow initiate play if before auction_info
+        check_treasury_account(program_id, treasury_account)?;
+        check_auction_config_account(program_id, auction_config_account)?;
+        let auction_config = AuctionConfig::unpack(&auction_config_account.data.borrow())?;
+        check_auction_info_account(program_id, &auction_config, auction_info_account)?;
+        check_game_state_account(program_id, game_state_account)?;
+        let mut game_state = GameState::unpack_unchecked(&game_state_account.data.borrow())?;
+
+        // Dont allow commits if after auction_end_slot
         let current_slot = Clock::from_account_info(sysvar_account)?.slot;
         let mut auction_info = AuctionInfo::unpack_unchecked(&auction_info_account.data.borrow())?;
-        if !auction_info.auction_enabled ||
-            auction_info.auction_end_slot >= current_slot {
-            msg!("Auction is active, cannot initiate play");
-            return Err(ProgramError::InvalidAccountData);
+        Self::update_game_phase(&mut game_state, &auction_info, &auction_config, current_slot);
+        if game_state.game_phase != GamePhase::Auction {
+            msg!("Auction is not active");
+            return Err(SixtyFourGameError::AuctionNotActive.into());
+        }
+        if !auction_config.is_sealed_bid() {
+            msg!("Auction takes open bids, use Bid");
+            return Err(SixtyFourGameError::InvalidBidMode.into());
+        }
+        if auction_info.bid_count >= auction_config.max_bid_count {
+            msg!("Bids have reached capacity");
+            return Err(SixtyFourGameError::AuctionFull.into());
         }
 
-        // Confirm mint account is for the square given
-
-        // Confirm mint is in the gameSquareList
+        // Deposit has to cover the amount revealed later, so it can be no less than the minimum bid
+        if deposit < auction_config.min_bid_lamports {
+            msg!("Deposit is below the minimum bid");
+            return Err(SixtyFourGameError::BidBelowMinimum.into());
+        }
 
-        // Initialize program's token account
-        let init_account_instr = spl_token::instruction::initialize_account(
-            &spl_token::ID,
-            program_token_account.key,
-            mint_account.key,
-            program_token_pda_account.key,
-        )?;
-        let init_account_account_infos = &[
-            program_token_account.clone(),
-            mint_account.clone(),
-            program_token_pda_account.clone(),
-            rent_account.clone()
-        ];
-        invoke_signed(
-            &init_account_instr,
-            init_account_account_infos,
-            &[],
+        // Transfer the deposit to the treasury, the part above the revealed amount is refunded
+        Self::collect_bid_funds(
+            &auction_config,
+            deposit,
+            bidder_account,
+            treasury_account,
+            system_program,
+            accounts_iter,
+            program_id,
         )?;
 
-        // Transfer NFT from player to program
-        let transfer_instr = spl_token::instruction::transfer(
-            &spl_token::ID,
-            game_square_token_account.key,
-            program_token_account.key,
-            player_account.key,
-            &[&player_account.key],
-            1,
-        )?;
-        let transfer_instr_account_infos = &[
-            game_square_token_account.clone(),
-            program_token_account.clone(),
-            player_account.clone(),
-            rent_account.clone()
-        ];
-        invoke_signed(
-            &transfer_instr,
-            transfer_instr_account_infos,
-            &[],
+        Self::open_auction_list_page(
+            &mut auction_info,
+            bidder_account,
+            auction_list_account,
+            system_program,
+            rent_account,
+            program_id,
         )?;
 
-        // Save active player info - designated spot on list for each square
-        let offset = (square * 72) as usize;
-        let mut active_player_list_info = ActivePlayer::unpack_unchecked(&active_players_list_account.data.borrow()[offset..(offset + 72)])?;
-        active_player_list_info.game_square_number = square;
-        active_player_list_info.owner_pubkey = *player_account.key;
-        active_player_list_info.program_token_account_pubkey = *program_token_account.key;
-        ActivePlayer::pack(active_player_list_info, &mut active_players_list_account.data.borrow_mut()[offset..(offset + 72)])?;
+        // Save a sealed BidEntry, it is ranked once revealed
+        let offset = auction_list_offset(auction_info.bid_count);
+        let mut auction_list_info = BidEntry::unpack_unchecked(&auction_list_account.data.borrow()[offset..(offset + BidEntry::LEN)])?;
+        auction_list_info.bid_number = auction_info.bid_count;
+        auction_list_info.amount_lamports = deposit;
+        auction_list_info.bidder_pubkey = *bidder_account.key;
+        auction_list_info.bid_status = BidStatus::Sealed;
+        auction_list_info.priority = auction_info.bid_sequence;
+        auction_list_info.commitment = commitment;
+        BidEntry::pack(auction_list_info, &mut auction_list_account.data.borrow_mut()[offset..(offset + BidEntry::LEN)])?;
+
+        auction_info.bid_count += 1;
+        auction_info.bid_sequence += 1;
+        auction_info.sealed_deposits += deposit;
+        AuctionInfo::pack(auction_info, &mut auction_info_account.data.borrow_mut())?;
 
-        msg!("Initiate Play / Deposit NFT successful");
+        msg!("Commit bid successful");
         Ok(())
     }
 
-    pub fn process_end_play(
+    pub fn process_reveal_bid(
         accounts: &[AccountInfo],
-        square: u64,
+        bid_number: u64,
+        amount: u64,
+        salt: [u8; 32],
         program_id: &Pubkey,
     ) -> ProgramResult {
 
-        let accounts_iter = &mut accounts.iter();
         // Set accounts
-        let player_account = next_account_info(accounts_iter)?;
-        let user_game_square_token_account = next_account_info(accounts_iter)?;
-        let game_square_token_account = next_account_info(accounts_iter)?;
+        let accounts_iter = &mut accounts.iter();
+        let bidder_account = next_account_info(accounts_iter)?;
+        let auction_list_account = next_account_info(accounts_iter)?;
+        let treasury_account = next_account_info(accounts_iter)?;
         let auction_info_account = next_account_info(accounts_iter)?;
         let sysvar_account = next_account_info(accounts_iter)?;
-        let mint_account = next_account_info(accounts_iter)?;
-        let program_token_pda_account = next_account_info(accounts_iter)?;
-        let rent_account = next_account_info(accounts_iter)?;
-        let spl_token_program = next_account_info(accounts_iter)?;
-        let active_players_list_account = next_account_info(accounts_iter)?;
-        let treasury_account = next_account_info(accounts_iter)?;
+        let auction_config_account = next_account_info(accounts_iter)?;
+        let game_state_account = next_account_info(accounts_iter)?;
+        let bid_index_account = next_account_info(accounts_iter)?;
 
-        // Confirm player is signer
-        if !player_account.is_signer {
+        // Confirm bidder is signer
+        if !bidder_account.is_signer {
             return Err(ProgramError::MissingRequiredSignature);
         }
 
-        // Dont allow end play if before auction_info
+        check_treasury_account(program_id, treasury_account)?;
+        check_auction_config_account(program_id, auction_config_account)?;
+        let auction_config = AuctionConfig::unpack(&auction_config_account.data.borrow())?;
+        check_auction_info_account(program_id, &auction_config, auction_info_account)?;
+        check_bid_index_account(&auction_config, bid_index_account)?;
+        check_game_state_account(program_id, game_state_account)?;
+        let mut game_state = GameState::unpack_unchecked(&game_state_account.data.borrow())?;
+
+        // Reveals are only taken between auction_end_slot and the end of the reveal window
         let current_slot = Clock::from_account_info(sysvar_account)?.slot;
         let mut auction_info = AuctionInfo::unpack_unchecked(&auction_info_account.data.borrow())?;
-        if !auction_info.auction_enabled ||
-            auction_info.auction_end_slot >= current_slot {
-            msg!("Auction is active, cannot initiate play");
-            return Err(ProgramError::InvalidAccountData);
+        Self::update_game_phase(&mut game_state, &auction_info, &auction_config, current_slot);
+        if game_state.game_phase != GamePhase::Reveal {
+            msg!("Reveal is not active");
+            return Err(SixtyFourGameError::RevealNotActive.into());
+        }
+        if bid_number >= auction_info.bid_count {
+            msg!("Bid number does not exist");
+            return Err(SixtyFourGameError::InvalidBidNumber.into());
         }
 
-        // Initialize users's token account
-        let init_account_instr = spl_token::instruction::initialize_account(
-            &spl_token::ID,
-            user_game_square_token_account.key,
+        // Confirm signer placed this bid and it is still sealed
+        check_auction_list_page_account(program_id, auction_list_page(bid_number), auction_list_account)?;
+        let offset = auction_list_offset(bid_number);
+        let mut auction_list_info = BidEntry::unpack_unchecked(&auction_list_account.data.borrow()[offset..(offset + BidEntry::LEN)])?;
+        if auction_list_info.bidder_pubkey != *bidder_account.key {
+            msg!("Trying to reveal a bid placed by a different bidder");
+            return Err(SixtyFourGameError::NotBidder.into());
+        }
+        if auction_list_info.bid_status != BidStatus::Sealed {
+            msg!("Bid is not sealed");
+            return Err(SixtyFourGameError::BidNotActive.into());
+        }
+
+        // Amount and salt have to match the commitment and be covered by the deposit
+        let deposit = auction_list_info.amount_lamports;
+        if bid_commitment(amount, &salt) != auction_list_info.commitment || amount > deposit {
+            msg!("Reveal does not match the committed bid");
+            return Err(SixtyFourGameError::InvalidBidReveal.into());
+        }
+        if amount < auction_config.min_bid_lamports {
+            msg!("Bid is below the minimum bid");
+            return Err(SixtyFourGameError::BidBelowMinimum.into());
+        }
+
+        // Refund the deposit above the revealed amount
+        Self::pay_bid_funds(&auction_config, deposit - amount, bidder_account, treasury_account, accounts_iter, program_id)?;
+
+        let priority = auction_list_info.priority;
+        auction_list_info.amount_lamports = amount;
+        auction_list_info.bid_status = BidStatus::Active;
+        BidEntry::pack(auction_list_info, &mut auction_list_account.data.borrow_mut()[offset..(offset + BidEntry::LEN)])?;
+
+        // Rank the revealed bid, ties go to the bid committed first
+        let rank = bid_index::insert(&mut bid_index_account.data.borrow_mut(), amount, priority, bid_number)?;
+        let rank_string: String = rank.to_string();
+        let rank_str: &str = &rank_string;
+        msg!("Bid ranked:");
+        msg!(rank_str);
+
+        auction_info.sealed_deposits -= deposit;
+        AuctionInfo::pack(auction_info, &mut auction_info_account.data.borrow_mut())?;
+
+        msg!("Reveal bid successful");
+        Ok(())
+    }
+
+    pub fn process_buy_square(
+        accounts: &[AccountInfo],
+        max_price: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+
+        // Set accounts
+        let accounts_iter = &mut accounts.iter();
+        let buyer_account = next_account_info(accounts_iter)?;
+        let auction_info_account = next_account_info(accounts_iter)?;
+        let sysvar_account = next_account_info(accounts_iter)?;
+        let mint_account = next_account_info(accounts_iter)?;
+        let token_account = next_account_info(accounts_iter)?;
+        let mint_pda_account = next_account_info(accounts_iter)?;
+        let rent_account = next_account_info(accounts_iter)?;
+        let spl_token_program = next_account_info(accounts_iter)?;
+        let all_game_squares_list_account = next_account_info(accounts_iter)?;
+        let treasury_account = next_account_info(accounts_iter)?;
+        let auction_config_account = next_account_info(accounts_iter)?;
+        let game_state_account = next_account_info(accounts_iter)?;
+        let system_program = next_account_info(accounts_iter)?;
+
+        // Confirm buyer is signer
+        if !buyer_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        check_treasury_account(program_id, treasury_account)?;
+        check_auction_config_account(program_id, auction_config_account)?;
+        let auction_config = AuctionConfig::unpack(&auction_config_account.data.borrow())?;
+        check_auction_info_account(program_id, &auction_config, auction_info_account)?;
+        check_all_game_squares_list_account(program_id, &auction_config, all_game_squares_list_account)?;
+        check_game_state_account(program_id, game_state_account)?;
+        let mut game_state = GameState::unpack_unchecked(&game_state_account.data.borrow())?;
+
+        let current_slot = Clock::from_account_info(sysvar_account)?.slot;
+        let mut auction_info = AuctionInfo::unpack_unchecked(&auction_info_account.data.borrow())?;
+        Self::update_game_phase(&mut game_state, &auction_info, &auction_config, current_slot);
+        if game_state.game_phase != GamePhase::Auction {
+            msg!("Auction is not active");
+            return Err(SixtyFourGameError::AuctionNotActive.into());
+        }
+        if auction_config.auction_mode != AuctionMode::Dutch {
+            msg!("Auction takes bids, use Bid");
+            return Err(SixtyFourGameError::InvalidBidMode.into());
+        }
+
+        // Price of the next square drops every slot since its sale opened
+        let price = auction_config.dutch_price(auction_info.price_start_slot, current_slot);
+        let price_string: String = price.to_string();
+        let price_str: &str = &price_string;
+        msg!("Square price:");
+        msg!(price_str);
+        if price > max_price {
+            msg!("Square price is above the max price");
+            return Err(SixtyFourGameError::PriceAboveMax.into());
+        }
+
+        // Transfer the price to the treasury, sales settle at once so there is nothing to refund
+        Self::collect_bid_funds(
+            &auction_config,
+            price,
+            buyer_account,
+            treasury_account,
+            system_program,
+            accounts_iter,
+            program_id,
+        )?;
+
+        Self::mint_game_square(
+            &auction_info,
+            &auction_config,
+            &mut game_state,
+            SquareMintAccounts {
+                owner: buyer_account,
+                mint: mint_account,
+                token_account,
+                mint_pda: mint_pda_account,
+                rent: rent_account,
+                spl_token_program,
+            },
+            all_game_squares_list_account,
+            program_id,
+        )?;
+        game_state.prize_pool += price;
+
+        // The next square goes on sale at the start price, play starts with the last square.
+        // The auction end moves to the last sale so Showdown counts from it
+        auction_info.squares_minted += 1;
+        auction_info.price_start_slot = current_slot;
+        if auction_info.squares_minted == auction_config.max_game_square_count() {
+            msg!("All squares sold, play started");
+            auction_info.auction_end_slot = current_slot;
+            game_state.game_phase = GamePhase::Play;
+        }
+        AuctionInfo::pack(auction_info, &mut auction_info_account.data.borrow_mut())?;
+        GameState::pack(game_state, &mut game_state_account.data.borrow_mut())?;
+
+        msg!("Buy square successful");
+        Ok(())
+    }
+
+    pub fn process_mint_nft(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+
+        let accounts_iter = &mut accounts.iter();
+        // Set accounts
+        let payer_account = next_account_info(accounts_iter)?;
+        let bid_entry_account = next_account_info(accounts_iter)?;
+        let auction_list_account = next_account_info(accounts_iter)?;
+        let auction_info_account = next_account_info(accounts_iter)?;
+        let sysvar_account = next_account_info(accounts_iter)?;
+        let mint_account = next_account_info(accounts_iter)?;
+        let token_account = next_account_info(accounts_iter)?;
+        let mint_pda_account = next_account_info(accounts_iter)?;
+        let rent_account = next_account_info(accounts_iter)?;
+        let spl_token_program = next_account_info(accounts_iter)?;
+        let all_game_squares_list_account = next_account_info(accounts_iter)?;
+        let treasury_account = next_account_info(accounts_iter)?;
+        let auction_config_account = next_account_info(accounts_iter)?;
+        let game_state_account = next_account_info(accounts_iter)?;
+        let bid_index_account = next_account_info(accounts_iter)?;
+
+        check_auction_config_account(program_id, auction_config_account)?;
+        let auction_config = AuctionConfig::unpack(&auction_config_account.data.borrow())?;
+        check_auction_info_account(program_id, &auction_config, auction_info_account)?;
+        check_all_game_squares_list_account(program_id, &auction_config, all_game_squares_list_account)?;
+        check_game_state_account(program_id, game_state_account)?;
+        let mut game_state = GameState::unpack_unchecked(&game_state_account.data.borrow())?;
+        check_bid_index_account(&auction_config, bid_index_account)?;
+
+        // Dont allow minting if before auction_info
+        let current_slot = Clock::from_account_info(sysvar_account)?.slot;
+        let mut auction_info = AuctionInfo::unpack_unchecked(&auction_info_account.data.borrow())?;
+        Self::update_game_phase(&mut game_state, &auction_info, &auction_config, current_slot);
+        match game_state.game_phase {
+            GamePhase::Minting => {},
+            GamePhase::Uninitialized => {
+                msg!("Auction has not started, cannot mint");
+                return Err(SixtyFourGameError::AuctionNotActive.into());
+            },
+            GamePhase::Auction => {
+                msg!("Auction is active, cannot mint");
+                return Err(SixtyFourGameError::AuctionStillRunning.into());
+            },
+            GamePhase::Reveal => {
+                msg!("Sealed bids are being revealed, cannot mint");
+                return Err(SixtyFourGameError::AuctionStillRunning.into());
+            },
+            _ => {
+                msg!("All squares have been minted, losing bids can use ClaimRefund");
+                return Err(SixtyFourGameError::AllSquaresMinted.into());
+            },
+        }
+
+        // Squares are minted in rank order, square N goes to the bid ranked N. Without a bid
+        // left that meets the reserve minting ends, play starts with the squares minted so far
+        let (highest_bid_amount_lamports, highest_bid_priority, highest_bid_bid_number) =
+            match Self::winning_bid(&auction_config, &bid_index_account.data.borrow(), auction_info.squares_minted) {
+                Some(entry) => entry,
+                None => {
+                    msg!("No bid left for this square, minting ended");
+                    Self::start_play(&mut game_state, &auction_info);
+                    GameState::pack(game_state, &mut game_state_account.data.borrow_mut())?;
+                    return Ok(());
+                },
+            };
+        check_auction_list_page_account(program_id, auction_list_page(highest_bid_bid_number), auction_list_account)?;
+        let bid_offset = auction_list_offset(highest_bid_bid_number);
+        let highest_bid_info = BidEntry::unpack_unchecked(&auction_list_account.data.borrow()[bid_offset..(bid_offset + BidEntry::LEN)])?;
+        if highest_bid_info.bid_number != highest_bid_bid_number ||
+            highest_bid_info.ranking_key() != (highest_bid_amount_lamports, highest_bid_priority) {
+            msg!("Bid index does not match the auction list");
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if highest_bid_info.bidder_pubkey != *bid_entry_account.key {
+            msg!("Trying to MintNFT for account that is not the higest bidder");
+            return Err(SixtyFourGameError::NotHighestBidder.into());
+        }
+
+        let game_square_number = Self::mint_game_square(
+            &auction_info,
+            &auction_config,
+            &mut game_state,
+            SquareMintAccounts {
+                owner: bid_entry_account,
+                mint: mint_account,
+                token_account,
+                mint_pda: mint_pda_account,
+                rent: rent_account,
+                spl_token_program,
+            },
+            all_game_squares_list_account,
+            program_id,
+        )?;
+
+        // Winning bids make up the prize, refunds and the treasury rent stay out of it
+        game_state.prize_pool += highest_bid_amount_lamports;
+
+        // Increment squares minted - tracks current minting, play starts with the last square
+        // or the last ranked bid
+        auction_info.squares_minted += 1;
+        if auction_info.squares_minted == auction_config.max_game_square_count() {
+            msg!("All squares minted");
+            Self::start_play(&mut game_state, &auction_info);
+        } else if Self::winning_bid(&auction_config, &bid_index_account.data.borrow(), auction_info.squares_minted).is_none() {
+            msg!("No bid left for the next square, minting ended");
+            Self::start_play(&mut game_state, &auction_info);
+        }
+        AuctionInfo::pack(auction_info, &mut auction_info_account.data.borrow_mut())?;
+        GameState::pack(game_state, &mut game_state_account.data.borrow_mut())?;
+
+        // Prevent refund of the winning bid and save its final standing
+        let mut auction_list_info = highest_bid_info;
+        auction_list_info.bid_status = BidStatus::Won;
+        auction_list_info.winning_rank = game_square_number;
+        BidEntry::pack(auction_list_info, &mut auction_list_account.data.borrow_mut()[bid_offset..(bid_offset + BidEntry::LEN)])?;
+
+        msg!("Mint NFT successful");
+
+        Ok(())
+    }
+
+    pub fn process_initiate_play(
+        accounts: &[AccountInfo],
+        square: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+
+        let accounts_iter = &mut accounts.iter();
+        // Set accounts
+        let player_account = next_account_info(accounts_iter)?;
+        let game_square_token_account = next_account_info(accounts_iter)?;
+        let auction_info_account = next_account_info(accounts_iter)?;
+        let sysvar_account = next_account_info(accounts_iter)?;
+        let mint_account = next_account_info(accounts_iter)?;
+        let program_token_pda_account = next_account_info(accounts_iter)?;
+        let program_token_account = next_account_info(accounts_iter)?;
+        let rent_account = next_account_info(accounts_iter)?;
+        let spl_token_program = next_account_info(accounts_iter)?;
+        let active_players_list_account = next_account_info(accounts_iter)?;
+        let treasury_account = next_account_info(accounts_iter)?;
+        let all_game_squares_list_account = next_account_info(accounts_iter)?;
+        let system_program = next_account_info(accounts_iter)?;
+        let auction_config_account = next_account_info(accounts_iter)?;
+        let game_state_account = next_account_info(accounts_iter)?;
+
+        // Confirm player is signer
+        if !player_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        check_auction_config_account(program_id, auction_config_account)?;
+        let auction_config = AuctionConfig::unpack(&auction_config_account.data.borrow())?;
+        check_auction_info_account(program_id, &auction_config, auction_info_account)?;
+        check_active_players_list_account(program_id, &auction_config, active_players_list_account)?;
+        check_game_state_account(program_id, game_state_account)?;
+        let mut game_state = GameState::unpack_unchecked(&game_state_account.data.borrow())?;
+        check_all_game_squares_list_account(program_id, &auction_config, all_game_squares_list_account)?;
+
+        // Dont allow initiate play before every square is minted or after the game is over
+        let current_slot = Clock::from_account_info(sysvar_account)?.slot;
+        let auction_info = AuctionInfo::unpack_unchecked(&auction_info_account.data.borrow())?;
+        Self::update_game_phase(&mut game_state, &auction_info, &auction_config, current_slot);
+        Self::check_game_in_play(&game_state)?;
+        if square >= auction_info.squares_minted {
+            msg!("Square has not been minted");
+            return Err(SixtyFourGameError::InvalidSquareNumber.into());
+        }
+
+        // Purged squares are out of the game for good
+        let square_offset = square as usize * GameSquare::LEN;
+        let game_square_info = GameSquare::unpack_unchecked(&all_game_squares_list_account.data.borrow()[square_offset..(square_offset + GameSquare::LEN)])?;
+        if game_square_info.purged {
+            msg!("Square was purged in the showdown, cannot initiate play");
+            return Err(SixtyFourGameError::SquarePurged.into());
+        }
+
+        // Confirm mint account is the square's mint in the gameSquareList
+        if *mint_account.key != game_square_info.mint_pubkey {
+            msg!("Mint account does not match the game square mint");
+            return Err(SixtyFourGameError::InvalidSquareMint.into());
+        }
+
+        // Confirm square is not already in play
+        let offset = square as usize * ActivePlayer::LEN;
+        let mut active_player_list_info = ActivePlayer::unpack_unchecked(&active_players_list_account.data.borrow()[offset..(offset + ActivePlayer::LEN)])?;
+        if active_player_list_info.is_active() {
+            msg!("Square is already active");
+            return Err(SixtyFourGameError::SquareActive.into());
+        }
+
+        // Create program's token account at the program address of the square and mint
+        let rent = Rent::from_account_info(rent_account)?;
+        check_program_token_owner(program_id, program_token_pda_account)?;
+        let token_account_bump_seed = check_program_token_account(program_id, square, mint_account.key, program_token_account)?;
+        create_program_address_account(
+            player_account,
+            program_token_account,
+            system_program,
+            &rent,
+            TokenAccount::LEN,
+            &[PROGRAM_TOKEN_ACCOUNT_SEED, &square.to_le_bytes(), mint_account.key.as_ref(), &[token_account_bump_seed]],
+            &spl_token::id(),
+        )?;
+
+        // Initialize program's token account
+        let init_account_instr = spl_token::instruction::initialize_account(
+            &spl_token::ID,
+            program_token_account.key,
+            mint_account.key,
+            program_token_pda_account.key,
+        )?;
+        let init_account_account_infos = &[
+            program_token_account.clone(),
+            mint_account.clone(),
+            program_token_pda_account.clone(),
+            rent_account.clone()
+        ];
+        invoke_signed(
+            &init_account_instr,
+            init_account_account_infos,
+            &[],
+        )?;
+
+        // Transfer NFT from player to program
+        let transfer_instr = spl_token::instruction::transfer(
+            &spl_token::ID,
+            game_square_token_account.key,
+            program_token_account.key,
+            player_account.key,
+            &[&player_account.key],
+            1,
+        )?;
+        let transfer_instr_account_infos = &[
+            game_square_token_account.clone(),
+            program_token_account.clone(),
+            player_account.clone(),
+            rent_account.clone()
+        ];
+        invoke_signed(
+            &transfer_instr,
+            transfer_instr_account_infos,
+            &[],
+        )?;
+
+        // Save active player info - designated spot on list for each square
+        active_player_list_info.game_square_number = square;
+        active_player_list_info.status = ActivePlayerStatus::Active;
+        active_player_list_info.owner_pubkey = *player_account.key;
+        active_player_list_info.program_token_account_pubkey = *program_token_account.key;
+        active_player_list_info.attack_pending = false;
+        ActivePlayer::pack(active_player_list_info, &mut active_players_list_account.data.borrow_mut()[offset..(offset + ActivePlayer::LEN)])?;
+
+        msg!("Initiate Play / Deposit NFT successful");
+        Ok(())
+    }
+
+    pub fn process_end_play(
+        accounts: &[AccountInfo],
+        square: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+
+        let accounts_iter = &mut accounts.iter();
+        // Set accounts
+        let player_account = next_account_info(accounts_iter)?;
+        let user_game_square_token_account = next_account_info(accounts_iter)?;
+        let game_square_token_account = next_account_info(accounts_iter)?;
+        let auction_info_account = next_account_info(accounts_iter)?;
+        let sysvar_account = next_account_info(accounts_iter)?;
+        let mint_account = next_account_info(accounts_iter)?;
+        let program_token_pda_account = next_account_info(accounts_iter)?;
+        let rent_account = next_account_info(accounts_iter)?;
+        let spl_token_program = next_account_info(accounts_iter)?;
+        let active_players_list_account = next_account_info(accounts_iter)?;
+        let treasury_account = next_account_info(accounts_iter)?;
+        let auction_config_account = next_account_info(accounts_iter)?;
+        let game_state_account = next_account_info(accounts_iter)?;
+        let all_game_squares_list_account = next_account_info(accounts_iter)?;
+
+        // Confirm player is signer
+        if !player_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        check_auction_config_account(program_id, auction_config_account)?;
+        let auction_config = AuctionConfig::unpack(&auction_config_account.data.borrow())?;
+        check_auction_info_account(program_id, &auction_config, auction_info_account)?;
+        check_active_players_list_account(program_id, &auction_config, active_players_list_account)?;
+        check_all_game_squares_list_account(program_id, &auction_config, all_game_squares_list_account)?;
+        check_game_state_account(program_id, game_state_account)?;
+        let mut game_state = GameState::unpack_unchecked(&game_state_account.data.borrow())?;
+
+        // Squares stay in the game once the showdown starts
+        let current_slot = Clock::from_account_info(sysvar_account)?.slot;
+        let auction_info = AuctionInfo::unpack_unchecked(&auction_info_account.data.borrow())?;
+        Self::update_game_phase(&mut game_state, &auction_info, &auction_config, current_slot);
+        match game_state.game_phase {
+            GamePhase::Play | GamePhase::GameOver | GamePhase::Settled => {},
+            GamePhase::Showdown => {
+                msg!("Showdown has started, cannot end play");
+                return Err(SixtyFourGameError::ShowdownStarted.into());
+            },
+            _ => {
+                msg!("Game has not started, cannot end play");
+                return Err(SixtyFourGameError::GameNotStarted.into());
+            },
+        }
+
+        if square >= auction_info.squares_minted {
+            msg!("Square has not been minted");
+            return Err(SixtyFourGameError::InvalidSquareNumber.into());
+        }
+
+        // Confirm signer owns the square in play, the winner owns a captured square
+        let active_offset = square as usize * ActivePlayer::LEN;
+        let mut active_player_info = ActivePlayer::unpack_unchecked(&active_players_list_account.data.borrow()[active_offset..(active_offset + ActivePlayer::LEN)])?;
+        if !active_player_info.is_active() {
+            msg!("Square is not active, nothing to withdraw");
+            return Err(SixtyFourGameError::SquareNotActive.into());
+        }
+        if active_player_info.owner_pubkey != *player_account.key {
+            msg!("Trying to end play for a square owned by a different player");
+            return Err(SixtyFourGameError::NotSquareOwner.into());
+        }
+
+        // Pending attacks have to be resolved before leaving play, they are void once the game is over
+        if active_player_info.attack_pending && game_state.game_phase == GamePhase::Play {
+            msg!("Square has a pending attack, cannot end play");
+            return Err(SixtyFourGameError::AttackPending.into());
+        }
+
+        // Confirm the program token account holds this square's NFT, captured squares keep
+        // the token account they were deposited into
+        let square_offset = square as usize * GameSquare::LEN;
+        let game_square_info = GameSquare::unpack_unchecked(&all_game_squares_list_account.data.borrow()[square_offset..(square_offset + GameSquare::LEN)])?;
+
+        // Once the game is over winners withdraw after claiming, ClaimPrize pays the owner in play
+        if game_state.game_phase == GamePhase::GameOver && !game_square_info.prize_claimed {
+            msg!("Claim the prize before ending play");
+            return Err(SixtyFourGameError::PrizeNotClaimed.into());
+        }
+        if *mint_account.key != game_square_info.mint_pubkey {
+            msg!("Mint account does not match the game square mint");
+            return Err(SixtyFourGameError::InvalidSquareMint.into());
+        }
+        check_program_token_account(program_id, square, mint_account.key, game_square_token_account)?;
+        if *game_square_token_account.key != active_player_info.program_token_account_pubkey {
+            msg!("Token account does not match the square's program token account");
+            return Err(SixtyFourGameError::InvalidProgramTokenAccount.into());
+        }
+        let program_token_account_info = TokenAccount::unpack(&game_square_token_account.data.borrow())?;
+        if program_token_account_info.mint != game_square_info.mint_pubkey {
+            msg!("Program token account holds a different mint");
+            return Err(SixtyFourGameError::InvalidProgramTokenAccount.into());
+        }
+
+        // Initialize users's token account
+        let init_account_instr = spl_token::instruction::initialize_account(
+            &spl_token::ID,
+            user_game_square_token_account.key,
             mint_account.key,
             player_account.key,
         )?;
@@ -481,7 +1350,7 @@ impl Processor {
         )?;
 
         // Transfer NFT from program to owner
-        let (_address, _bump_seed) = Pubkey::find_program_address(&[b"initiate"], &program_id);
+        let _bump_seed = check_program_token_owner(program_id, program_token_pda_account)?;
         let transfer_instr = spl_token::instruction::transfer(
             &spl_token::ID,
             game_square_token_account.key,
@@ -498,7 +1367,7 @@ impl Processor {
         ];
 
         let signer_seeds: &[&[_]] = &[
-            b"initiate",
+            PROGRAM_TOKEN_OWNER_SEED,
             &[_bump_seed],
         ];
 
@@ -508,15 +1377,29 @@ impl Processor {
             &[&signer_seeds],
         )?;
 
-        // Remove ownerKey from Active Players
-        let fromOffsetActive = (square * 72) as usize;
-        let mut attacker_active_player_info = ActivePlayer::unpack_unchecked(&active_players_list_account.data.borrow()[fromOffsetActive..(fromOffsetActive + 72)])?;
-
-        // Placeholders for now...
-        attacker_active_player_info.game_square_number = 999;
-        attacker_active_player_info.owner_pubkey = spl_token::ID;
+        // Close the empty program token account, rent goes back to the player
+        let close_instr = spl_token::instruction::close_account(
+            &spl_token::ID,
+            game_square_token_account.key,
+            player_account.key,
+            program_token_pda_account.key,
+            &[],
+        )?;
+        invoke_signed(
+            &close_instr,
+            &[
+                game_square_token_account.clone(),
+                player_account.clone(),
+                program_token_pda_account.clone(),
+            ],
+            &[&signer_seeds],
+        )?;
 
-        ActivePlayer::pack(attacker_active_player_info, &mut active_players_list_account.data.borrow_mut()[fromOffsetActive..(fromOffsetActive + 72)])?;
+        // Remove ownerKey from Active Players
+        active_player_info.status = ActivePlayerStatus::Inactive;
+        active_player_info.owner_pubkey = Pubkey::default();
+        active_player_info.program_token_account_pubkey = Pubkey::default();
+        ActivePlayer::pack(active_player_info, &mut active_players_list_account.data.borrow_mut()[active_offset..(active_offset + ActivePlayer::LEN)])?;
 
         msg!("End Play / Withdraw NFT successful");
         Ok(())
@@ -527,6 +1410,7 @@ impl Processor {
         amount: u64,
         from_square_index: u64,
         to_square_index: u64,
+        commitment: Hash,
         program_id: &Pubkey,
     ) -> ProgramResult {
 
@@ -535,164 +1419,202 @@ impl Processor {
         let attacker_owner_account = next_account_info(accounts_iter)?;
         let auction_info_account = next_account_info(accounts_iter)?;
         let sysvar_account = next_account_info(accounts_iter)?;
-        let sysvar_slot_history = next_account_info(accounts_iter)?;
-        let rent_account = next_account_info(accounts_iter)?;
-        let spl_token_program = next_account_info(accounts_iter)?;
         let active_players_list_account = next_account_info(accounts_iter)?;
         let all_game_squares_list_account = next_account_info(accounts_iter)?;
-        let treasury_account = next_account_info(accounts_iter)?;
+        let auction_config_account = next_account_info(accounts_iter)?;
+        let game_state_account = next_account_info(accounts_iter)?;
+
+        check_auction_config_account(program_id, auction_config_account)?;
+        let auction_config = AuctionConfig::unpack(&auction_config_account.data.borrow())?;
+        check_auction_info_account(program_id, &auction_config, auction_info_account)?;
+        check_active_players_list_account(program_id, &auction_config, active_players_list_account)?;
+        check_all_game_squares_list_account(program_id, &auction_config, all_game_squares_list_account)?;
+        check_game_state_account(program_id, game_state_account)?;
+        let mut game_state = GameState::unpack_unchecked(&game_state_account.data.borrow())?;
 
         // Confirm attacker is signer
         if !attacker_owner_account.is_signer {
             return Err(ProgramError::MissingRequiredSignature);
         }
 
-        // Dont allow initiate play if before auction_info
+        // Dont allow attacks before every square is minted or after the game is over
         let current_slot = Clock::from_account_info(sysvar_account)?.slot;
-        let mut auction_info = AuctionInfo::unpack_unchecked(&auction_info_account.data.borrow())?;
-        if !auction_info.auction_enabled ||
-            auction_info.auction_end_slot >= current_slot {
-            msg!("Auction is active, cannot attack");
-            return Err(ProgramError::InvalidAccountData);
+        let auction_info = AuctionInfo::unpack_unchecked(&auction_info_account.data.borrow())?;
+        Self::update_game_phase(&mut game_state, &auction_info, &auction_config, current_slot);
+        Self::check_game_in_play(&game_state)?;
+        if from_square_index >= auction_info.squares_minted || to_square_index >= auction_info.squares_minted {
+            msg!("Square has not been minted");
+            return Err(SixtyFourGameError::InvalidSquareNumber.into());
         }
 
         // Confirm attacker can attack defender square
-        let mut attackable = [[0u64; 4]; 64];
-        attackable = [
-            [2,4,6,8],
-            [1,3,9,11],
-            [2,4,12,14],
-            [1,3,5,15],
-            [4,6,16,18],
-            [1,5,7,19],
-            [6,8,20,22],
-            [1,7,9,23],
-            [2,8,10,24],
-            [9,11,25,27],
-            [2,10,12,28],
-            [3,11,13,29],
-            [12,14,30,32],
-            [3,13,15,33],
-            [4,14,16,34],
-            [5,15,17,35],
-            [16,18,36,38],
-            [5,17,19,39],
-            [6,18,20,40],
-            [7,19,21,41],
-            [20,22,42,44],
-            [7,21,23,45],
-            [8,22,24,46],
-            [9,23,25,47],
-            [10,24,26,48],
-            [25,27,49,51],
-            [10,26,28,52],
-            [11,27,29,53],
-            [12,28,30,54],
-            [13,29,31,55],
-            [32,30,56,58],
-            [13,31,33,59],
-            [14,32,34,60],
-            [15,33,35,61],
-            [16,34,36,62],
-            [17,35,37,63],
-            [36,38,64,0],
-            [17,37,39,0],
-            [18,38,40,0],
-            [19,39,41,0],
-            [20,40,42,0],
-            [21,41,43,0],
-            [42,44,0,0],
-            [21,43,45,0],
-            [22,44,46,0],
-            [23,45,47,0],
-            [24,46,48,0],
-            [25,47,49,0],
-            [26,48,50,0],
-            [49,51,0,0],
-            [26,50,52,0],
-            [27,51,53,0],
-            [28,52,54,0],
-            [29,53,55,0],
-            [30,54,56,0],
-            [31,55,57,0],
-            [56,58,0,0],
-            [31,57,59,0],
-            [32,58,60,0],
-            [33,59,61,0],
-            [34,60,62,0],
-            [35,61,63,0],
-            [36,62,64,0],
-            [37,63,0,0]
-        ];
-        let mut can_attack = false;
-        for (i, row) in attackable.iter().enumerate() {
-            for (j, col) in row.iter().enumerate() {
-                if i == from_square_index as usize && (*col - 1) == to_square_index {
-                    msg!("You can attack!");
-                    can_attack = true;
-                    break;
-                }
-            }
-        }
-        if (!can_attack) {
+        if !board::is_neighbor(auction_config.board_side, from_square_index, to_square_index) {
             msg!("Unable to attack, attacker and defender are not neighbors");
-            return Err(ProgramError::InvalidAccountData);  // TODO
+            return Err(SixtyFourGameError::NotNeighbors.into());
         }
 
         // Get attacker/defender info
-        let mut fromOffset = (from_square_index * 56) as usize;
-        let mut attacker_info = GameSquare::unpack_unchecked(&all_game_squares_list_account.data.borrow()[fromOffset..(fromOffset + 56)])?;
-        let mut toOffset = (to_square_index * 56) as usize;
-        let mut defender_info = GameSquare::unpack_unchecked(&all_game_squares_list_account.data.borrow()[toOffset..(toOffset + 56)])?;
+        let fromOffset = from_square_index as usize * GameSquare::LEN;
+        let attacker_info = GameSquare::unpack_unchecked(&all_game_squares_list_account.data.borrow()[fromOffset..(fromOffset + GameSquare::LEN)])?;
+        let toOffset = to_square_index as usize * GameSquare::LEN;
+        let defender_info = GameSquare::unpack_unchecked(&all_game_squares_list_account.data.borrow()[toOffset..(toOffset + GameSquare::LEN)])?;
 
         // Confirm attacker is on a different team than defender
         if attacker_info.team_number == defender_info.team_number {
             msg!("Unable to attack, attacker and defender are on the same team");
-            return Err(ProgramError::InvalidAccountData);  // TODO
+            return Err(SixtyFourGameError::SameTeam.into());
         }
 
         // Get active player accounts
-        let fromOffsetActive = (from_square_index * 72) as usize;
-        let mut attacker_active_player_info = ActivePlayer::unpack_unchecked(&active_players_list_account.data.borrow()[fromOffsetActive..(fromOffsetActive + 72)])?;
+        let fromOffsetActive = from_square_index as usize * ActivePlayer::LEN;
+        let mut attacker_active_player_info = ActivePlayer::unpack_unchecked(&active_players_list_account.data.borrow()[fromOffsetActive..(fromOffsetActive + ActivePlayer::LEN)])?;
+
+        let toOffsetActive = to_square_index as usize * ActivePlayer::LEN;
+        let defender_active_player_info = ActivePlayer::unpack_unchecked(&active_players_list_account.data.borrow()[toOffsetActive..(toOffsetActive + ActivePlayer::LEN)])?;
+
+        // Confirm attacker matches attacker owner_pubkey
+        if *attacker_owner_account.key != attacker_active_player_info.owner_pubkey {
+            msg!("Unable to attack, transaction signer does not match attacker");
+            return Err(SixtyFourGameError::NotSquareOwner.into());
+        }
+
+        // Confirm attacker is active
+        if !attacker_active_player_info.is_active() {
+            msg!("Unable to attack, attacker is not active");
+            return Err(SixtyFourGameError::AttackerInactive.into());
+        }
+        // Confirm defender is active
+        if !defender_active_player_info.is_active() {
+            msg!("Unable to attack, defender is not active");
+            return Err(SixtyFourGameError::DefenderInactive.into());
+        }
+
+        // One attack at a time per square
+        if attacker_active_player_info.attack_pending {
+            msg!("Unable to attack, attacker has a pending attack");
+            return Err(SixtyFourGameError::AttackPending.into());
+        }
+
+        // Commit the attack, the roll uses the hash of a slot that does not exist yet
+        attacker_active_player_info.attack_pending = true;
+        attacker_active_player_info.attack_to_square = to_square_index;
+        attacker_active_player_info.attack_amount = amount;
+        attacker_active_player_info.attack_commit_slot = current_slot;
+        attacker_active_player_info.attack_commitment = commitment;
+        ActivePlayer::pack(attacker_active_player_info, &mut active_players_list_account.data.borrow_mut()[fromOffsetActive..(fromOffsetActive + ActivePlayer::LEN)])?;
+
+        let resolve_slot: String = (current_slot + ATTACK_SLOT_DELAY).to_string();
+        let resolve_slot_str: &str = &resolve_slot;
+        msg!("Attack committed, resolves with the hash of the first slot from:");
+        msg!(resolve_slot_str);
+
+        msg!("Attack successful");
+        Ok(())
+    }
+
+    pub fn process_resolve_attack(
+        accounts: &[AccountInfo],
+        from_square_index: u64,
+        secret: [u8; 32],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
 
-        let toOffsetActive = (to_square_index * 72) as usize;
-        let mut defender_active_player_info = ActivePlayer::unpack_unchecked(&active_players_list_account.data.borrow()[toOffsetActive..(toOffsetActive + 72)])?;
+        // Set accounts
+        let accounts_iter = &mut accounts.iter();
+        let auction_info_account = next_account_info(accounts_iter)?;
+        let sysvar_account = next_account_info(accounts_iter)?;
+        let sysvar_slot_hashes = next_account_info(accounts_iter)?;
+        let active_players_list_account = next_account_info(accounts_iter)?;
+        let all_game_squares_list_account = next_account_info(accounts_iter)?;
+        let auction_config_account = next_account_info(accounts_iter)?;
+        let game_state_account = next_account_info(accounts_iter)?;
+
+        check_auction_config_account(program_id, auction_config_account)?;
+        let auction_config = AuctionConfig::unpack(&auction_config_account.data.borrow())?;
+        check_auction_info_account(program_id, &auction_config, auction_info_account)?;
+        check_active_players_list_account(program_id, &auction_config, active_players_list_account)?;
+        check_all_game_squares_list_account(program_id, &auction_config, all_game_squares_list_account)?;
+        check_game_state_account(program_id, game_state_account)?;
+        let mut game_state = GameState::unpack_unchecked(&game_state_account.data.borrow())?;
+
+        // Confirm account is the SlotHashes sysvar
+        if *sysvar_slot_hashes.key != sysvar::slot_hashes::id() {
+            msg!("Slot hashes account is not the SlotHashes sysvar");
+            return Err(SixtyFourGameError::InvalidSlotHashesAccount.into());
+        }
 
-        // Confirm attacker matches attacker owner_pubkey
-        if *attacker_owner_account.key != attacker_active_player_info.owner_pubkey {
-            msg!("Unable to attack, transaction signer does not match attacker");
-            return Err(ProgramError::InvalidAccountData);  // TODO
+        // Attacks left pending when the game ended are void
+        let current_slot = Clock::from_account_info(sysvar_account)?.slot;
+        let auction_info = AuctionInfo::unpack_unchecked(&auction_info_account.data.borrow())?;
+        Self::update_game_phase(&mut game_state, &auction_info, &auction_config, current_slot);
+        Self::check_game_in_play(&game_state)?;
+        if from_square_index >= auction_info.squares_minted {
+            msg!("Square has not been minted");
+            return Err(SixtyFourGameError::InvalidSquareNumber.into());
         }
 
-        // Confirm attacker is active
-        if attacker_active_player_info.game_square_number != from_square_index {
-            msg!("Unable to attack, attacker is not active");
-            return Err(ProgramError::InvalidAccountData);  // TODO
+        // Get the pending attack
+        let fromOffsetActive = from_square_index as usize * ActivePlayer::LEN;
+        let mut attacker_active_player_info = ActivePlayer::unpack_unchecked(&active_players_list_account.data.borrow()[fromOffsetActive..(fromOffsetActive + ActivePlayer::LEN)])?;
+        if !attacker_active_player_info.attack_pending {
+            msg!("Square has no pending attack");
+            return Err(SixtyFourGameError::NoPendingAttack.into());
         }
-        // Confirm defender is active
-        if defender_active_player_info.game_square_number != to_square_index {
-            msg!("Unable to attack, defender is not active");
-            return Err(ProgramError::InvalidAccountData);  // TODO
+        let to_square_index = attacker_active_player_info.attack_to_square;
+        let amount = attacker_active_player_info.attack_amount;
+        let resolve_slot = attacker_active_player_info.attack_commit_slot + ATTACK_SLOT_DELAY;
+        if current_slot <= resolve_slot {
+            msg!("Slot hash for the attack is not available yet");
+            return Err(SixtyFourGameError::AttackNotReady.into());
         }
 
-        // Get the roll under value TODO: based on rank
-        let advantage_percent = 4;
-        let roll_under_64 = 51 + ((auction_info.squares_minted - attacker_info.game_square_number) * advantage_percent / auction_info.squares_minted) as u64;
+        // Get result of attack, an attack that was not revealed in time misses
+        let result = if current_slot > attacker_active_player_info.attack_commit_slot + ATTACK_REVEAL_WINDOW {
+            msg!("Attack was not revealed in time, attacker misses");
+            100
+        } else {
+            if attack_commitment(&secret) != attacker_active_player_info.attack_commitment {
+                msg!("Secret does not match the attack commitment");
+                return Err(SixtyFourGameError::InvalidAttackSecret.into());
+            }
+            let slot_hashes_data = sysvar_slot_hashes.try_borrow_data()?;
+            let (hash_slot, slot_hash) = find_slot_hash(&slot_hashes_data, resolve_slot)
+                .ok_or(SixtyFourGameError::SlotHashUnavailable)?;
+
+            let hs: String = hash_slot.to_string();
+            let hss: &str = &hs;
+            msg!("Rolling with the hash of slot:");
+            msg!(hss);
 
-        // Get result of attack (hash blockhash, get rand value from 1-100)
-        let slot_hashes_data = sysvar_slot_history.try_borrow_data()?;
-        let slot_hash = get_slot_hash(&slot_hashes_data, current_slot - 3);
+            attack_roll(&secret, &slot_hash, from_square_index, to_square_index)
+        };
 
-        // Confirm account is slotHashes account & hash is not empty
+        attacker_active_player_info.attack_pending = false;
+
+        // Get attacker/defender info
+        let fromOffset = from_square_index as usize * GameSquare::LEN;
+        let mut attacker_info = GameSquare::unpack_unchecked(&all_game_squares_list_account.data.borrow()[fromOffset..(fromOffset + GameSquare::LEN)])?;
+        let toOffset = to_square_index as usize * GameSquare::LEN;
+        let mut defender_info = GameSquare::unpack_unchecked(&all_game_squares_list_account.data.borrow()[toOffset..(toOffset + GameSquare::LEN)])?;
+
+        let toOffsetActive = to_square_index as usize * ActivePlayer::LEN;
+        let mut defender_active_player_info = ActivePlayer::unpack_unchecked(&active_players_list_account.data.borrow()[toOffsetActive..(toOffsetActive + ActivePlayer::LEN)])?;
+
+        // Defender may have withdrawn or a capture may have changed teams since the commit
+        if !defender_active_player_info.is_active() ||
+            attacker_info.team_number == defender_info.team_number {
+            msg!("Defender is no longer attackable, attack is void");
+            ActivePlayer::pack(attacker_active_player_info, &mut active_players_list_account.data.borrow_mut()[fromOffsetActive..(fromOffsetActive + ActivePlayer::LEN)])?;
+            return Ok(());
+        }
 
-        // Get roll result
-        let val = hash_value(slot_hash);
-        let result = (val % 100) + 1;
+        // Get the roll under value based on attacker rank
+        let roll_under = board::roll_under(auction_config.max_game_square_count(), attacker_info.game_square_number);
 
         // Log result
         let s: String = result.to_string();
         let ss: &str = &s;
-        let un: String = roll_under_64.to_string();
+        let un: String = roll_under.to_string();
         let uns: &str = &un;
         msg!("Rolling for a number under:");
         msg!(uns);
@@ -700,7 +1622,7 @@ impl Processor {
         msg!(ss);
 
         // Decrease health of attacker or defender based on result
-        if result >= roll_under_64 {
+        if result >= roll_under {
             msg!("You LOSE! Attacker loses health");
 
             // Check if health is going to go to 0, if so, update ActivePlayer ownerKey to
@@ -708,15 +1630,17 @@ impl Processor {
             if (attacker_info.health_number <= amount) {
                 msg!("Attacker dies...");
                 // reset health for new player
-                attacker_info.health_number = 100000000;
+                attacker_info.health_number = auction_config.starting_health;
 
-                // Transfer ownership
+                // Transfer ownership, the NFT stays in the square's program token account
+                // until the new owner withdraws it with EndPlay
                 attacker_active_player_info.owner_pubkey = defender_active_player_info.owner_pubkey;
-                attacker_active_player_info.program_token_account_pubkey = defender_active_player_info.program_token_account_pubkey;
-
-                ActivePlayer::pack(attacker_active_player_info, &mut active_players_list_account.data.borrow_mut()[fromOffsetActive..(fromOffsetActive + 72)])?;
+                attacker_active_player_info.status = ActivePlayerStatus::Captured;
+                attacker_active_player_info.clear_attack();
 
                 // Transfer Team
+                game_state.team_square_counts[attacker_info.team_number as usize] -= 1;
+                game_state.team_square_counts[defender_info.team_number as usize] += 1;
                 attacker_info.team_number = defender_info.team_number;
 
             } else {
@@ -731,15 +1655,19 @@ impl Processor {
             if (defender_info.health_number <= amount) {
                 msg!("Defender dies...");
                 // reset health for new player
-                defender_info.health_number = 100000000;
+                defender_info.health_number = auction_config.starting_health;
 
-                // Transfer ownership
+                // Transfer ownership, the NFT stays in the square's program token account
+                // until the new owner withdraws it with EndPlay
                 defender_active_player_info.owner_pubkey = attacker_active_player_info.owner_pubkey;
-                defender_active_player_info.program_token_account_pubkey = attacker_active_player_info.program_token_account_pubkey;
+                defender_active_player_info.status = ActivePlayerStatus::Captured;
+                defender_active_player_info.clear_attack();
 
-                ActivePlayer::pack(defender_active_player_info, &mut active_players_list_account.data.borrow_mut()[toOffsetActive..(toOffsetActive + 72)])?;
+                ActivePlayer::pack(defender_active_player_info, &mut active_players_list_account.data.borrow_mut()[toOffsetActive..(toOffsetActive + ActivePlayer::LEN)])?;
 
                 // Transfer Team
+                game_state.team_square_counts[defender_info.team_number as usize] -= 1;
+                game_state.team_square_counts[attacker_info.team_number as usize] += 1;
                 defender_info.team_number = attacker_info.team_number;
 
             } else {
@@ -748,48 +1676,15 @@ impl Processor {
 
         }
 
-        GameSquare::pack(attacker_info, &mut all_game_squares_list_account.data.borrow_mut()[fromOffset..(fromOffset + 56)])?;
-        GameSquare::pack(defender_info, &mut all_game_squares_list_account.data.borrow_mut()[toOffset..(toOffset + 56)])?;
-
-        // Check for winner - TODO: save this in account?
-        let mut red = 0;  //0
-        let mut blue = 0;  //1
-        let mut green = 0;  //2
-        let mut orange = 0;  //3
-        for i in 0..auction_info.squares_minted {
-            let mut fromOffset = (i * 56) as usize;
-            let mut square_info = GameSquare::unpack_unchecked(&all_game_squares_list_account.data.borrow()[fromOffset..(fromOffset + 56)])?;
-            if square_info.team_number == 0 {
-                red += 1;
-            }
-            if square_info.team_number == 1 {
-                blue += 1;
-            }
-            if square_info.team_number == 2 {
-                green += 1;
-            }
-            if square_info.team_number == 3 {
-                orange += 1;
-            }
-        }
-
-        if red == auction_info.squares_minted ||
-           blue == auction_info.squares_minted ||
-           green == auction_info.squares_minted ||
-           orange == auction_info.squares_minted {
-
-            msg!("Game over! Changing team numbers");
+        ActivePlayer::pack(attacker_active_player_info, &mut active_players_list_account.data.borrow_mut()[fromOffsetActive..(fromOffsetActive + ActivePlayer::LEN)])?;
+        GameSquare::pack(attacker_info, &mut all_game_squares_list_account.data.borrow_mut()[fromOffset..(fromOffset + GameSquare::LEN)])?;
+        GameSquare::pack(defender_info, &mut all_game_squares_list_account.data.borrow_mut()[toOffset..(toOffset + GameSquare::LEN)])?;
 
-            // Set team number to specific number to signify game over
-            for i in 0..auction_info.squares_minted {
-                let mut fromOffset = (i * 56) as usize;
-                let mut game_square_info = GameSquare::unpack_unchecked(&all_game_squares_list_account.data.borrow()[fromOffset..(fromOffset + 56)])?;
-                game_square_info.team_number = GAME_OVER_TEAM_NUMBER_UNCLAIMED;
-                GameSquare::pack(game_square_info, &mut all_game_squares_list_account.data.borrow_mut()[fromOffset..(fromOffset + 56)])?;
-            }
-        }
+        // Check for winner
+        Self::check_game_over(&mut game_state, &auction_info);
+        GameState::pack(game_state, &mut game_state_account.data.borrow_mut())?;
 
-        msg!("Attack successful");
+        msg!("Resolve attack successful");
         Ok(())
     }
 
@@ -811,50 +1706,668 @@ impl Processor {
         let active_players_list_account = next_account_info(accounts_iter)?;
         let all_game_squares_list_account = next_account_info(accounts_iter)?;
         let treasury_account = next_account_info(accounts_iter)?;
-
-        // Dont allow claim prize if before auction
-        let current_slot = Clock::from_account_info(sysvar_account)?.slot;
-        let mut auction_info = AuctionInfo::unpack_unchecked(&auction_info_account.data.borrow())?;
-        if !auction_info.auction_enabled ||
-            auction_info.auction_end_slot >= current_slot {
-            msg!("Auction is active, cannot claim prize");
-            return Err(ProgramError::InvalidAccountData);  //TODO
+        let game_state_account = next_account_info(accounts_iter)?;
+        let auction_config_account = next_account_info(accounts_iter)?;
+
+        check_treasury_account(program_id, treasury_account)?;
+        check_auction_config_account(program_id, auction_config_account)?;
+        let auction_config = AuctionConfig::unpack(&auction_config_account.data.borrow())?;
+        check_auction_info_account(program_id, &auction_config, auction_info_account)?;
+        check_active_players_list_account(program_id, &auction_config, active_players_list_account)?;
+        check_all_game_squares_list_account(program_id, &auction_config, all_game_squares_list_account)?;
+        check_game_state_account(program_id, game_state_account)?;
+        let mut game_state = GameState::unpack_unchecked(&game_state_account.data.borrow())?;
+
+        // Prizes are only paid out once the game is over
+        match game_state.game_phase {
+            GamePhase::GameOver => {},
+            GamePhase::Settled => {
+                msg!("Every prize was already claimed");
+                return Err(SixtyFourGameError::PrizeAlreadyClaimed.into());
+            },
+            _ => {
+                msg!("Game is not over, cannot claim prize");
+                return Err(SixtyFourGameError::GameNotOver.into());
+            },
+        }
+        let auction_info = AuctionInfo::unpack_unchecked(&auction_info_account.data.borrow())?;
+        if square >= auction_info.squares_minted {
+            msg!("Square has not been minted");
+            return Err(SixtyFourGameError::InvalidSquareNumber.into());
         }
 
         // Check prize is claimable
-        let mut fromOffset = (square * 56) as usize;
-        let mut game_square_info = GameSquare::unpack_unchecked(&all_game_squares_list_account.data.borrow()[fromOffset..(fromOffset + 56)])?;
-        if (game_square_info.team_number != GAME_OVER_TEAM_NUMBER_UNCLAIMED) {
-            msg!("Team number of game square not equal to GAME_OVER_TEAM_NUMBER_UNCLAIMED. Game may not be over or prize was already claimed.");
-            return Err(ProgramError::InvalidAccountData);  //TODO
+        let mut fromOffset = square as usize * GameSquare::LEN;
+        let mut game_square_info = GameSquare::unpack_unchecked(&all_game_squares_list_account.data.borrow()[fromOffset..(fromOffset + GameSquare::LEN)])?;
+        if game_square_info.purged {
+            msg!("Game square was purged in the showdown, no prize");
+            return Err(SixtyFourGameError::SquarePurged.into());
+        }
+        if game_square_info.team_number != game_state.winning_team {
+            msg!("Game square is not on the winning team");
+            return Err(SixtyFourGameError::NotWinningTeam.into());
+        }
+        if game_square_info.prize_claimed {
+            msg!("Prize for this game square was already claimed");
+            return Err(SixtyFourGameError::PrizeAlreadyClaimed.into());
         }
 
-        // Check owner matches
-        let offset = (square * 72) as usize;
-        let mut active_player_info = ActivePlayer::unpack_unchecked(&active_players_list_account.data.borrow()[offset..(offset + 72)])?;
-        if (active_player_info.owner_pubkey != *claimer_account.key) {
-            msg!("Trying to claim a prize for a different atcive player owner");
-            return Err(ProgramError::InvalidAccountData);  //TODO
+        // Check owner matches, the owner in play for a square in play, otherwise the holder of
+        // the square's NFT so squares withdrawn or never deposited still get their share
+        let offset = square as usize * ActivePlayer::LEN;
+        let active_player_info = ActivePlayer::unpack_unchecked(&active_players_list_account.data.borrow()[offset..(offset + ActivePlayer::LEN)])?;
+        if active_player_info.is_active() {
+            if active_player_info.owner_pubkey != *claimer_account.key {
+                msg!("Trying to claim a prize for a different atcive player owner");
+                return Err(SixtyFourGameError::NotSquareOwner.into());
+            }
+        } else {
+            let claimer_square_token_account = next_account_info(accounts_iter)?;
+            check_square_holder(claimer_square_token_account, &game_square_info.mint_pubkey, claimer_account.key)?;
         }
 
         msg!("Claiming prize!");
+        let winning_squares = auction_info.squares_minted - game_state.squares_purged;
+        let takes_remainder = Self::is_first_square_in_game(&all_game_squares_list_account.data.borrow(), square)?;
+        let payout_amount = Self::claim_prize_share(&mut game_state, winning_squares, takes_remainder);
 
-        // Trasnfer prize amount to player
-        let payout_amount = (treasury_account.lamports() / auction_info.squares_minted) as u64;
-        **treasury_account.lamports.borrow_mut() -= payout_amount;
-        **claimer_account.lamports.borrow_mut() += payout_amount;
-
-        // Set team number to claimed
-        game_square_info.team_number = GAME_OVER_TEAM_NUMBER_CLAIMED;
-        GameSquare::pack(game_square_info, &mut all_game_squares_list_account.data.borrow_mut()[fromOffset..(fromOffset + 56)])?;
+        // Trasnfer prize amount to player, purged squares get no share
+        Self::pay_bid_funds(&auction_config, payout_amount, claimer_account, treasury_account, accounts_iter, program_id)?;
 
-        // Decrement squares_minted to give each winner same %
-        auction_info.squares_minted -= 1;  // TODO: use different var for this
-        AuctionInfo::pack(auction_info, &mut auction_info_account.data.borrow_mut())?;
+        // Record the claim so it can only be paid once
+        game_square_info.prize_claimed = true;
+        GameSquare::pack(game_square_info, &mut all_game_squares_list_account.data.borrow_mut()[fromOffset..(fromOffset + GameSquare::LEN)])?;
+        GameState::pack(game_state, &mut game_state_account.data.borrow_mut())?;
 
         msg!("Claim prize successful");
         Ok(())
     }
+
+    pub fn process_claim_refund(
+        accounts: &[AccountInfo],
+        bid_number: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+
+        // Set accounts
+        let accounts_iter = &mut accounts.iter();
+        let bidder_account = next_account_info(accounts_iter)?;
+        let auction_list_account = next_account_info(accounts_iter)?;
+        let treasury_account = next_account_info(accounts_iter)?;
+        let auction_info_account = next_account_info(accounts_iter)?;
+        let auction_config_account = next_account_info(accounts_iter)?;
+        let game_state_account = next_account_info(accounts_iter)?;
+
+        check_treasury_account(program_id, treasury_account)?;
+        check_auction_config_account(program_id, auction_config_account)?;
+        check_game_state_account(program_id, game_state_account)?;
+        let game_state = GameState::unpack_unchecked(&game_state_account.data.borrow())?;
+
+        // Dont allow refunds until minting ended, every square minted or the ranked bids ran out
+        match game_state.game_phase {
+            GamePhase::Uninitialized | GamePhase::Auction | GamePhase::Reveal | GamePhase::Minting => {
+                msg!("Auction has not been resolved, cannot claim refund");
+                return Err(SixtyFourGameError::AuctionNotResolved.into());
+            },
+            _ => {},
+        }
+        let mut auction_info = AuctionInfo::unpack_unchecked(&auction_info_account.data.borrow())?;
+        if bid_number >= auction_info.bid_count {
+            msg!("Bid number does not exist");
+            return Err(SixtyFourGameError::InvalidBidNumber.into());
+        }
+
+        // Confirm refund goes to the bidder of a losing bid
+        check_auction_list_page_account(program_id, auction_list_page(bid_number), auction_list_account)?;
+        let offset = auction_list_offset(bid_number);
+        let mut auction_list_info = BidEntry::unpack_unchecked(&auction_list_account.data.borrow()[offset..(offset + BidEntry::LEN)])?;
+        if auction_list_info.bidder_pubkey != *bidder_account.key {
+            msg!("Trying to claim a refund for a different bidder");
+            return Err(SixtyFourGameError::NotBidder.into());
+        }
+        let auction_config = AuctionConfig::unpack(&auction_config_account.data.borrow())?;
+        check_auction_info_account(program_id, &auction_config, auction_info_account)?;
+        match auction_list_info.bid_status {
+            BidStatus::Active => {},
+            BidStatus::Sealed if auction_config.forfeit_unrevealed && auction_info.squares_minted > 0 => {
+                msg!("Bid was not revealed, the deposit is forfeited");
+                return Err(SixtyFourGameError::BidForfeited.into());
+            },
+            // Unrevealed deposits are refunded in full
+            BidStatus::Sealed => {
+                auction_info.sealed_deposits -= auction_list_info.amount_lamports;
+                AuctionInfo::pack(auction_info, &mut auction_info_account.data.borrow_mut())?;
+            },
+            BidStatus::Won => {
+                msg!("Bid won a square, nothing to refund");
+                return Err(SixtyFourGameError::BidWon.into());
+            },
+            BidStatus::Cancelled | BidStatus::Refunded => {
+                msg!("Bid has already been refunded");
+                return Err(SixtyFourGameError::BidNotActive.into());
+            },
+        }
+
+        // Refund bid amount from treasury
+        Self::pay_bid_funds(&auction_config, auction_list_info.amount_lamports, bidder_account, treasury_account, accounts_iter, program_id)?;
+
+        // Record the refund so it can only be paid once
+        auction_list_info.bid_status = BidStatus::Refunded;
+        BidEntry::pack(auction_list_info, &mut auction_list_account.data.borrow_mut()[offset..(offset + BidEntry::LEN)])?;
+
+        msg!("Claim refund successful");
+        Ok(())
+    }
+
+    pub fn process_purge_inactive(
+        accounts: &[AccountInfo],
+        square: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+
+        // Set accounts
+        let accounts_iter = &mut accounts.iter();
+        let auction_info_account = next_account_info(accounts_iter)?;
+        let sysvar_account = next_account_info(accounts_iter)?;
+        let auction_config_account = next_account_info(accounts_iter)?;
+        let game_state_account = next_account_info(accounts_iter)?;
+        let active_players_list_account = next_account_info(accounts_iter)?;
+        let all_game_squares_list_account = next_account_info(accounts_iter)?;
+
+        // Anyone can purge, the status bytes are only trusted from the pinned accounts
+        check_auction_config_account(program_id, auction_config_account)?;
+        let auction_config = AuctionConfig::unpack(&auction_config_account.data.borrow())?;
+        check_auction_info_account(program_id, &auction_config, auction_info_account)?;
+        check_active_players_list_account(program_id, &auction_config, active_players_list_account)?;
+        check_all_game_squares_list_account(program_id, &auction_config, all_game_squares_list_account)?;
+        check_game_state_account(program_id, game_state_account)?;
+        let mut game_state = GameState::unpack_unchecked(&game_state_account.data.borrow())?;
+
+        // Only purge once the showdown started
+        let current_slot = Clock::from_account_info(sysvar_account)?.slot;
+        let auction_info = AuctionInfo::unpack_unchecked(&auction_info_account.data.borrow())?;
+        Self::update_game_phase(&mut game_state, &auction_info, &auction_config, current_slot);
+        Self::check_game_in_play(&game_state)?;
+        if game_state.game_phase != GamePhase::Showdown {
+            msg!("Showdown has not started, cannot purge");
+            return Err(SixtyFourGameError::ShowdownNotStarted.into());
+        }
+        if square >= auction_info.squares_minted {
+            msg!("Square has not been minted");
+            return Err(SixtyFourGameError::InvalidSquareNumber.into());
+        }
+
+        // Confirm square is still in the game and not in play
+        let offset = square as usize * GameSquare::LEN;
+        let mut game_square_info = GameSquare::unpack_unchecked(&all_game_squares_list_account.data.borrow()[offset..(offset + GameSquare::LEN)])?;
+        if game_square_info.purged {
+            msg!("Square was already purged");
+            return Err(SixtyFourGameError::SquarePurged.into());
+        }
+        let active_offset = square as usize * ActivePlayer::LEN;
+        let active_player_info = ActivePlayer::unpack_unchecked(&active_players_list_account.data.borrow()[active_offset..(active_offset + ActivePlayer::LEN)])?;
+        if active_player_info.is_active() {
+            msg!("Square is active, cannot purge");
+            return Err(SixtyFourGameError::SquareActive.into());
+        }
+
+        // Remove square from the game, it no longer counts towards any team
+        Self::purge_square(&mut game_state, &auction_info, game_square_info.team_number)?;
+        game_square_info.purged = true;
+        GameSquare::pack(game_square_info, &mut all_game_squares_list_account.data.borrow_mut()[offset..(offset + GameSquare::LEN)])?;
+        GameState::pack(game_state, &mut game_state_account.data.borrow_mut())?;
+
+        msg!("Purge inactive successful");
+        Ok(())
+    }
+
+    /// Errors unless the bid meets the minimum bid, enters the winning ranks by the minimum
+    /// increment once they are all taken and meets the reserve price of the rank it enters at.
+    /// Top ups of a bid already in the winning ranks skip the increment. Returns the rank
+    fn check_bid_amount(
+        auction_config: &AuctionConfig,
+        bid_index_data: &[u8],
+        amount: u64,
+        priority: u64,
+        was_winning: bool,
+    ) -> Result<u64, ProgramError> {
+        if amount < auction_config.min_bid_lamports {
+            msg!("Bid is below the minimum bid");
+            return Err(SixtyFourGameError::BidBelowMinimum.into());
+        }
+        let last_winning_rank = auction_config.max_game_square_count() - 1;
+        if !was_winning {
+            if let Some((last_winning_amount, _, _)) = bid_index::get(bid_index_data, last_winning_rank) {
+                let increment = auction_config.min_increment_lamports.max(1);
+                if amount < last_winning_amount.saturating_add(increment) {
+                    msg!("Bid has to beat the last winning bid by the minimum increment");
+                    return Err(SixtyFourGameError::BidBelowIncrement.into());
+                }
+            }
+        }
+        let rank = bid_index::rank(bid_index_data, amount, priority);
+        if amount < auction_config.reserve_price(rank) {
+            msg!("Bid is below the reserve price of its rank");
+            return Err(SixtyFourGameError::BidBelowReserve.into());
+        }
+        Ok(rank)
+    }
+
+    /// Bid index entry winning the square ranked `rank`, none once the bids run out. Open bids
+    /// met the reserve of the rank they entered at in Bid or IncreaseBid and keep it when
+    /// cancelled bids move them up. Sealed bids are only ranked once revealed, a sealed bid
+    /// below the reserve of its rank wins no square and neither do the bids ranked after it
+    fn winning_bid(
+        auction_config: &AuctionConfig,
+        bid_index_data: &[u8],
+        rank: u64,
+    ) -> Option<(u64, u64, u64)> {
+        let (amount, priority, bid_number) = bid_index::get(bid_index_data, rank)?;
+        if auction_config.is_sealed_bid() && amount < auction_config.reserve_price(rank) {
+            return None;
+        }
+        Some((amount, priority, bid_number))
+    }
+
+    /// Mints the next game square NFT to owner_account and saves its GameSquare, shared by
+    /// MintNFT and BuySquare. Returns the game square number
+    fn mint_game_square(
+        auction_info: &AuctionInfo,
+        auction_config: &AuctionConfig,
+        game_state: &mut GameState,
+        mint_accounts: SquareMintAccounts,
+        all_game_squares_list_account: &AccountInfo,
+        program_id: &Pubkey,
+    ) -> Result<u64, ProgramError> {
+        let SquareMintAccounts {
+            owner: owner_account,
+            mint: mint_account,
+            token_account,
+            mint_pda: mint_pda_account,
+            rent: rent_account,
+            spl_token_program,
+        } = mint_accounts;
+        // Mint authority is the program address, no one else can mint more of the square
+        let (mint_address, mint_bump_seed) = Pubkey::find_program_address(&[b"mint"], &program_id);
+        if *mint_pda_account.key != mint_address {
+            msg!("Mint authority account does not match the program address");
+            return Err(SixtyFourGameError::InvalidMintAuthority.into());
+        }
+
+        // Inititalize mint - program
+        let mint_instr = spl_token::instruction::initialize_mint(
+            &spl_token::ID,
+            mint_account.key,
+            mint_pda_account.key,
+            Option::Some(mint_pda_account.key),
+            0
+        )?;
+        let account_infos = &[
+            mint_account.clone(),
+            spl_token_program.clone(),
+            rent_account.clone(),
+            mint_pda_account.clone()
+        ];
+        invoke_signed(
+            &mint_instr,
+            account_infos,
+            &[],
+        )?;
+
+        // Initialize token account
+        let init_account_instr = spl_token::instruction::initialize_account(
+            &spl_token::ID,
+            token_account.key,
+            mint_account.key,
+            owner_account.key,
+        )?;
+        let init_account_account_infos = &[
+            token_account.clone(),
+            mint_account.clone(),
+            owner_account.clone(),
+            rent_account.clone()
+        ];
+        invoke_signed(
+            &init_account_instr,
+            init_account_account_infos,
+            &[],
+        )?;
+
+        // Mint token to bidder
+        let mint_to_instr = spl_token::instruction::mint_to(
+            &spl_token::ID,
+            mint_account.key,
+            token_account.key,
+            mint_pda_account.key,
+            &[],
+            1,
+        )?;
+        let account_infos = &[
+            mint_account.clone(),
+            token_account.clone(),
+            spl_token_program.clone(),
+            mint_pda_account.clone()
+        ];
+        let mint_signer_seeds: &[&[_]] = &[
+            b"mint",
+            &[mint_bump_seed],
+        ];
+        invoke_signed(
+            &mint_to_instr,
+            account_infos,
+            &[&mint_signer_seeds],
+        )?;
+
+        // Save a GameSquare into the all game squares list account
+        let offset = auction_info.squares_minted as usize * GameSquare::LEN;
+        let mut all_game_squares_list_info = GameSquare::unpack_unchecked(&all_game_squares_list_account.data.borrow()[offset..(offset + GameSquare::LEN)])?;
+        let game_square_number = auction_info.squares_minted;
+        all_game_squares_list_info.game_square_number = game_square_number;
+        all_game_squares_list_info.team_number = game_square_number % auction_config.team_count;
+        game_state.team_square_counts[all_game_squares_list_info.team_number as usize] += 1;
+        all_game_squares_list_info.health_number = auction_config.starting_health;
+        all_game_squares_list_info.mint_pubkey = *mint_account.key;
+        GameSquare::pack(all_game_squares_list_info, &mut all_game_squares_list_account.data.borrow_mut()[offset..(offset + GameSquare::LEN)])?;
+
+        Ok(game_square_number)
+    }
+
+    /// Moves bid funds from the payer into the treasury. With a bid mint they move from the
+    /// payer's token account into the bid escrow instead, see instruction::with_bid_token_accounts
+    fn collect_bid_funds<'a>(
+        auction_config: &AuctionConfig,
+        amount: u64,
+        payer_account: &AccountInfo<'a>,
+        treasury_account: &AccountInfo<'a>,
+        system_program: &AccountInfo<'a>,
+        accounts_iter: &mut std::slice::Iter<AccountInfo<'a>>,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        if !auction_config.is_token_bid() {
+            let transfer_instr = system_instruction::transfer(
+                payer_account.key,
+                treasury_account.key,
+                amount,
+            );
+            return invoke(
+                &transfer_instr,
+                &[
+                    payer_account.clone(),
+                    treasury_account.clone(),
+                    system_program.clone(),
+                ],
+            );
+        }
+
+        // Token program checks the payer owns the token account and the mints match
+        let payer_token_account = next_account_info(accounts_iter)?;
+        let bid_escrow_account = next_account_info(accounts_iter)?;
+        let _program_token_pda_account = next_account_info(accounts_iter)?;
+        let spl_token_program = next_account_info(accounts_iter)?;
+        check_bid_escrow_account(program_id, bid_escrow_account)?;
+        let transfer_instr = spl_token::instruction::transfer(
+            &spl_token::ID,
+            payer_token_account.key,
+            bid_escrow_account.key,
+            payer_account.key,
+            &[],
+            amount,
+        )?;
+        invoke(
+            &transfer_instr,
+            &[
+                payer_token_account.clone(),
+                bid_escrow_account.clone(),
+                payer_account.clone(),
+                spl_token_program.clone(),
+            ],
+        )
+    }
+
+    /// Pays bid funds out of the treasury to the recipient. With a bid mint they are paid out
+    /// of the bid escrow into a token account of the bid mint owned by the recipient instead
+    fn pay_bid_funds<'a>(
+        auction_config: &AuctionConfig,
+        amount: u64,
+        recipient_account: &AccountInfo<'a>,
+        treasury_account: &AccountInfo<'a>,
+        accounts_iter: &mut std::slice::Iter<AccountInfo<'a>>,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        if !auction_config.is_token_bid() {
+            **treasury_account.lamports.borrow_mut() -= amount;
+            **recipient_account.lamports.borrow_mut() += amount;
+            return Ok(());
+        }
+
+        let recipient_token_account = next_account_info(accounts_iter)?;
+        let bid_escrow_account = next_account_info(accounts_iter)?;
+        let program_token_pda_account = next_account_info(accounts_iter)?;
+        let spl_token_program = next_account_info(accounts_iter)?;
+        check_bid_escrow_account(program_id, bid_escrow_account)?;
+        let owner_bump_seed = check_program_token_owner(program_id, program_token_pda_account)?;
+        let recipient_token_info = TokenAccount::unpack(&recipient_token_account.data.borrow())?;
+        if recipient_token_info.owner != *recipient_account.key || recipient_token_info.mint != auction_config.bid_mint_pubkey {
+            msg!("Token account is not a bid mint account of the recipient");
+            return Err(SixtyFourGameError::InvalidBidTokenAccount.into());
+        }
+        let transfer_instr = spl_token::instruction::transfer(
+            &spl_token::ID,
+            bid_escrow_account.key,
+            recipient_token_account.key,
+            program_token_pda_account.key,
+            &[],
+            amount,
+        )?;
+        let signer_seeds: &[&[_]] = &[
+            PROGRAM_TOKEN_OWNER_SEED,
+            &[owner_bump_seed],
+        ];
+        invoke_signed(
+            &transfer_instr,
+            &[
+                bid_escrow_account.clone(),
+                recipient_token_account.clone(),
+                program_token_pda_account.clone(),
+                spl_token_program.clone(),
+            ],
+            &[&signer_seeds],
+        )
+    }
+
+    /// Bids fill the auction list pages in order, the bidder opening a page pays its rent
+    fn open_auction_list_page<'a>(
+        auction_info: &mut AuctionInfo,
+        bidder_account: &AccountInfo<'a>,
+        auction_list_account: &AccountInfo<'a>,
+        system_program: &AccountInfo<'a>,
+        rent_account: &AccountInfo<'a>,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let page = auction_list_page(auction_info.bid_count);
+        let page_bump_seed = check_auction_list_page_account(program_id, page, auction_list_account)?;
+        if page == auction_info.page_count {
+            let rent = Rent::from_account_info(rent_account)?;
+            create_program_address_account(
+                bidder_account,
+                auction_list_account,
+                system_program,
+                &rent,
+                BIDS_PER_AUCTION_LIST_PAGE as usize * BidEntry::LEN,
+                &[AUCTION_LIST_SEED, &page.to_le_bytes(), &[page_bump_seed]],
+                program_id,
+            )?;
+            auction_info.page_count += 1;
+        }
+        Ok(())
+    }
+
+    /// Soft close, a bid entering the winning ranks close to the end extends the auction
+    /// by extension_slots, up to max_auction_end_slot
+    fn apply_soft_close(
+        auction_info: &mut AuctionInfo,
+        auction_config: &AuctionConfig,
+        rank: u64,
+        current_slot: u64,
+    ) {
+        if rank < auction_config.max_game_square_count() &&
+            auction_info.auction_end_slot < current_slot.saturating_add(auction_config.soft_close_slots) &&
+            auction_info.auction_end_slot < auction_config.max_auction_end_slot {
+            auction_info.auction_end_slot = auction_info.auction_end_slot
+                .saturating_add(auction_config.extension_slots)
+                .min(auction_config.max_auction_end_slot);
+            let end_slot_string: String = auction_info.auction_end_slot.to_string();
+            let end_slot_str: &str = &end_slot_string;
+            msg!("Auction extended, auction end slot is now:");
+            msg!(end_slot_str);
+        }
+    }
+
+    /// Moves the game into Minting once the auction ends, through Reveal for sealed bid auctions,
+    /// or straight into Play for Dutch auctions, and into Showdown once showdown_delay_slots have
+    /// passed since the auction and reveal end
+    fn update_game_phase(
+        game_state: &mut GameState,
+        auction_info: &AuctionInfo,
+        auction_config: &AuctionConfig,
+        current_slot: u64,
+    ) {
+        // Dutch auctions end once every square is bought or at auction_end_slot, squares left
+        // unsold stay out of the game
+        if game_state.game_phase == GamePhase::Auction && current_slot > auction_info.auction_end_slot {
+            if auction_config.auction_mode == AuctionMode::Dutch {
+                msg!("Dutch auction ended");
+                Self::start_play(game_state, auction_info);
+            } else if auction_config.is_sealed_bid() {
+                msg!("Auction ended, reveal started");
+                game_state.game_phase = GamePhase::Reveal;
+            } else {
+                msg!("Auction ended");
+                game_state.game_phase = GamePhase::Minting;
+            }
+        }
+        let reveal_end_slot = auction_info.auction_end_slot.saturating_add(auction_config.reveal_slots);
+        if game_state.game_phase == GamePhase::Reveal && current_slot > reveal_end_slot {
+            msg!("Reveal ended");
+            game_state.game_phase = GamePhase::Minting;
+
+            // Deposits still sealed are forfeited into the prize
+            if auction_config.forfeit_unrevealed {
+                game_state.prize_pool += auction_info.sealed_deposits;
+            }
+        }
+        let showdown_slot = reveal_end_slot.saturating_add(auction_config.showdown_delay_slots);
+        if game_state.game_phase == GamePhase::Play && current_slot > showdown_slot {
+            msg!("Showdown started");
+            game_state.game_phase = GamePhase::Showdown;
+        }
+    }
+
+    /// Ends minting, squares left without a winning bid stay out of the game and their
+    /// losing bids can be refunded. Fewer squares than teams can end the game at once, no
+    /// square at all settles it with every deposit refundable, forfeited ones included
+    fn start_play(
+        game_state: &mut GameState,
+        auction_info: &AuctionInfo,
+    ) {
+        if auction_info.squares_minted == 0 {
+            msg!("No square minted, game settled");
+            game_state.game_phase = GamePhase::Settled;
+            game_state.prize_pool = 0;
+            return;
+        }
+        msg!("Play started");
+        game_state.game_phase = GamePhase::Play;
+        Self::check_game_over(game_state, auction_info);
+    }
+
+    /// Counts a prize claim and returns its payout. Every winning square gets the share
+    /// snapshotted at game over, the first square left in the game also takes the rounding
+    /// remainder so the whole prize pool is paid out whatever the claim order. The last claim
+    /// settles the game
+    fn claim_prize_share(
+        game_state: &mut GameState,
+        winning_squares: u64,
+        takes_remainder: bool,
+    ) -> u64 {
+        game_state.prizes_claimed += 1;
+        let mut payout_amount = game_state.prize_share;
+        if takes_remainder {
+            payout_amount += game_state.prize_pool - game_state.prize_share * winning_squares;
+        }
+        if game_state.prizes_claimed == winning_squares {
+            msg!("Every prize claimed, game settled");
+            game_state.game_phase = GamePhase::Settled;
+        }
+        payout_amount
+    }
+
+    /// True if every square numbered below `square` was purged, once the game is over the
+    /// squares left in the game are the winning squares
+    fn is_first_square_in_game(
+        all_game_squares_data: &[u8],
+        square: u64,
+    ) -> Result<bool, ProgramError> {
+        for square_number in 0..square {
+            let offset = square_number as usize * GameSquare::LEN;
+            let game_square_info = GameSquare::unpack_unchecked(&all_game_squares_data[offset..(offset + GameSquare::LEN)])?;
+            if !game_square_info.purged {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Errors unless squares can still be played, from Play until the game is over
+    fn check_game_in_play(game_state: &GameState) -> ProgramResult {
+        match game_state.game_phase {
+            GamePhase::Play | GamePhase::Showdown => Ok(()),
+            GamePhase::GameOver | GamePhase::Settled => {
+                msg!("Game is over");
+                Err(SixtyFourGameError::GameOver.into())
+            },
+            _ => {
+                msg!("Game has not started, every square has to be minted first");
+                Err(SixtyFourGameError::GameNotStarted.into())
+            },
+        }
+    }
+
+    /// Takes a square of team_number out of the game. Purging the last square of a team can
+    /// end the game, the last square in the game is never purged so the prize always has a winner
+    fn purge_square(
+        game_state: &mut GameState,
+        auction_info: &AuctionInfo,
+        team_number: u64,
+    ) -> ProgramResult {
+        if auction_info.squares_minted - game_state.squares_purged <= 1 {
+            msg!("Last square in the game cannot be purged");
+            return Err(SixtyFourGameError::LastSquareInGame.into());
+        }
+        game_state.squares_purged += 1;
+        game_state.team_square_counts[team_number as usize] -= 1;
+        Self::check_game_over(game_state, auction_info);
+        Ok(())
+    }
+
+    /// Ends the game once every square that was not purged is on the same team
+    fn check_game_over(
+        game_state: &mut GameState,
+        auction_info: &AuctionInfo,
+    ) {
+        let squares_in_game = auction_info.squares_minted - game_state.squares_purged;
+        if squares_in_game == 0 {
+            return;
+        }
+        if let Some(winning_team) = game_state.team_square_counts.iter().position(|count| *count == squares_in_game) {
+            msg!("Game over!");
+            game_state.game_phase = GamePhase::GameOver;
+            game_state.winning_team = winning_team as u64;
+
+            // Snapshot the share of every winning square
+            game_state.prize_share = game_state.prize_pool / squares_in_game;
+        }
+    }
 }
 
 
@@ -862,35 +2375,339 @@ impl Processor {
 #[cfg(test)]
 mod test {
     use super::*;
-    use solana_sdk::clock::Epoch;
+    use crate::test_runtime::{self, TestAccount};
+    use solana_sdk::{system_program, sysvar};
+
+    fn auction_config(board_side: u64) -> AuctionConfig {
+        let mut auction_config = AuctionConfig::unpack_unchecked(&[0; AuctionConfig::LEN]).unwrap();
+        auction_config.board_side = board_side;
+        auction_config
+    }
+
+    fn auction_info(auction_end_slot: u64) -> AuctionInfo {
+        let mut auction_info = AuctionInfo::unpack_unchecked(&[0; AuctionInfo::LEN]).unwrap();
+        auction_info.auction_end_slot = auction_end_slot;
+        auction_info
+    }
 
     #[test]
     fn test_sanity() {
+        // Missing or unknown instruction tags fail before any account is read
         let program_id = Pubkey::default();
-        let key = Pubkey::default();
-        let mut lamports = 0;
-        let mut data = vec![0; mem::size_of::<u64>()];
-        LittleEndian::write_u64(&mut data, 0);
-        let owner = Pubkey::default();
-        let account = AccountInfo::new(
-            &key,
-            false,
-            true,
-            &mut lamports,
-            &mut data,
-            &owner,
-            false,
-            Epoch::default(),
+        assert_eq!(
+            Processor::process(&program_id, &[], &[]),
+            Err(SixtyFourGameError::InvalidInstruction.into())
+        );
+        assert_eq!(
+            Processor::process(&program_id, &[], &[255]),
+            Err(SixtyFourGameError::InvalidInstruction.into())
+        );
+    }
+
+    #[test]
+    fn test_check_bid_amount() {
+        let mut auction_config = auction_config(2);
+        auction_config.min_bid_lamports = 10;
+        auction_config.reserve_price_lamports = 100;
+        auction_config.reserve_step_lamports = 20;
+        auction_config.min_increment_lamports = 5;
+        let mut data = vec![0; bid_index::bid_index_space(8)];
+
+        assert_eq!(
+            Processor::check_bid_amount(&auction_config, &data, 9, 0, false),
+            Err(SixtyFourGameError::BidBelowMinimum.into())
+        );
+        // Rank 0 reserve is 100, every rank after drops by 20
+        assert_eq!(
+            Processor::check_bid_amount(&auction_config, &data, 99, 0, false),
+            Err(SixtyFourGameError::BidBelowReserve.into())
+        );
+        assert_eq!(Processor::check_bid_amount(&auction_config, &data, 100, 0, false), Ok(0));
+        bid_index::insert(&mut data, 100, 0, 0).unwrap();
+        assert_eq!(Processor::check_bid_amount(&auction_config, &data, 80, 1, false), Ok(1));
+        assert_eq!(
+            Processor::check_bid_amount(&auction_config, &data, 79, 1, false),
+            Err(SixtyFourGameError::BidBelowReserve.into())
+        );
+        bid_index::insert(&mut data, 80, 1, 1).unwrap();
+        bid_index::insert(&mut data, 60, 2, 2).unwrap();
+        bid_index::insert(&mut data, 40, 3, 3).unwrap();
+
+        // Every rank is taken, a bid has to beat the last winning bid by the increment
+        assert_eq!(
+            Processor::check_bid_amount(&auction_config, &data, 44, 4, false),
+            Err(SixtyFourGameError::BidBelowIncrement.into())
+        );
+        assert_eq!(Processor::check_bid_amount(&auction_config, &data, 45, 4, false), Ok(3));
+        assert_eq!(Processor::check_bid_amount(&auction_config, &data, 200, 4, false), Ok(0));
+
+        // Winning bid 3 topped up by 1 with a losing bid of 39 right behind it, taken out of
+        // the index it would have to beat 39 by the increment
+        bid_index::insert(&mut data, 39, 4, 4).unwrap();
+        bid_index::remove(&mut data, 40, 3, 3).unwrap();
+        assert_eq!(
+            Processor::check_bid_amount(&auction_config, &data, 41, 3, false),
+            Err(SixtyFourGameError::BidBelowIncrement.into())
+        );
+        assert_eq!(Processor::check_bid_amount(&auction_config, &data, 41, 3, true), Ok(3));
+    }
+
+    #[test]
+    fn test_winning_bid() {
+        let mut auction_config = auction_config(2);
+        auction_config.reserve_price_lamports = 100;
+        auction_config.reserve_step_lamports = 20;
+        let mut data = vec![0; bid_index::bid_index_space(8)];
+        bid_index::insert(&mut data, 100, 0, 0).unwrap();
+        bid_index::insert(&mut data, 90, 1, 1).unwrap();
+        bid_index::insert(&mut data, 70, 2, 2).unwrap();
+        assert_eq!(Processor::winning_bid(&auction_config, &data, 0), Some((100, 0, 0)));
+        assert_eq!(Processor::winning_bid(&auction_config, &data, 1), Some((90, 1, 1)));
+        assert_eq!(Processor::winning_bid(&auction_config, &data, 3), None);
+
+        // Bid 1 cancelled, bid 2 moves up to rank 1 and keeps the reserve of 60 it entered at
+        bid_index::remove(&mut data, 90, 1, 1).unwrap();
+        assert_eq!(Processor::winning_bid(&auction_config, &data, 1), Some((70, 2, 2)));
+
+        // Sealed bids are held to the reserve of the rank they are revealed into
+        auction_config.reveal_slots = 10;
+        assert_eq!(Processor::winning_bid(&auction_config, &data, 0), Some((100, 0, 0)));
+        assert_eq!(Processor::winning_bid(&auction_config, &data, 1), None);
+        auction_config.reserve_price_lamports = 0;
+        assert_eq!(Processor::winning_bid(&auction_config, &data, 1), Some((70, 2, 2)));
+    }
+
+    #[test]
+    fn test_prize_share() {
+        let mut game_state = GameState::unpack_unchecked(&[0; GameState::LEN]).unwrap();
+        let mut auction_info = auction_info(0);
+        auction_info.squares_minted = 4;
+        game_state.game_phase = GamePhase::Showdown;
+        game_state.prize_pool = 1000;
+        game_state.team_square_counts[1] = 2;
+        game_state.team_square_counts[2] = 1;
+        game_state.squares_purged = 1;
+        Processor::check_game_over(&mut game_state, &auction_info);
+        assert_eq!(game_state.game_phase, GamePhase::Showdown);
+
+        // Purged squares are out of the split
+        game_state.team_square_counts[2] = 0;
+        game_state.squares_purged = 2;
+        Processor::check_game_over(&mut game_state, &auction_info);
+        assert_eq!(game_state.game_phase, GamePhase::GameOver);
+        assert_eq!(game_state.winning_team, 1);
+
+        // Odd pool, the first square in the game takes the remainder whenever it claims and
+        // the last claim settles the game
+        game_state.prize_pool = 1001;
+        game_state.prize_share = 1001 / 2;
+        assert_eq!(Processor::claim_prize_share(&mut game_state, 2, true), 501);
+        assert_eq!(game_state.game_phase, GamePhase::GameOver);
+        assert_eq!(Processor::claim_prize_share(&mut game_state, 2, false), 500);
+        assert_eq!(game_state.game_phase, GamePhase::Settled);
+
+        let mut data = vec![0; 3 * GameSquare::LEN];
+        for square in 0..3 {
+            let mut game_square = GameSquare::unpack_unchecked(&[0; GameSquare::LEN]).unwrap();
+            game_square.game_square_number = square;
+            game_square.purged = square == 0;
+            GameSquare::pack(game_square, &mut data[square as usize * GameSquare::LEN..(square as usize + 1) * GameSquare::LEN]).unwrap();
+        }
+        assert_eq!(Processor::is_first_square_in_game(&data, 0), Ok(true));
+        assert_eq!(Processor::is_first_square_in_game(&data, 1), Ok(true));
+        assert_eq!(Processor::is_first_square_in_game(&data, 2), Ok(false));
+    }
+
+    #[test]
+    fn test_bid_opens_funded_auction_list_page() {
+        let program_id = test_runtime::install();
+        let rent = Rent::default();
+        let page_space = BIDS_PER_AUCTION_LIST_PAGE as usize * BidEntry::LEN;
+        let amount = 1_000;
+
+        let auction_info_key = Pubkey::new_unique();
+        let bid_index_key = Pubkey::new_unique();
+        let mut auction_config = auction_config(2);
+        auction_config.is_initialized = true;
+        auction_config.max_bid_count = 4;
+        auction_config.auction_info_pubkey = auction_info_key;
+        auction_config.bid_index_pubkey = bid_index_key;
+        let mut config_data = vec![0; AuctionConfig::LEN];
+        AuctionConfig::pack(auction_config, &mut config_data).unwrap();
+        let mut info_data = vec![0; AuctionInfo::LEN];
+        AuctionInfo::pack(auction_info(100), &mut info_data).unwrap();
+        let mut game_state = GameState::unpack_unchecked(&[0; GameState::LEN]).unwrap();
+        game_state.game_phase = GamePhase::Auction;
+        let mut game_state_data = vec![0; GameState::LEN];
+        GameState::pack(game_state, &mut game_state_data).unwrap();
+
+        let address = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &program_id).0;
+        let mut bidder = TestAccount::new(Pubkey::new_unique(), 10_000_000_000, vec![], system_program::id());
+        let mut treasury = TestAccount::new(address(&[TREASURY_SEED]), 0, vec![], program_id);
+        let mut auction_info_account = TestAccount::new(auction_info_key, 0, info_data, program_id);
+        let mut clock = TestAccount::sysvar(sysvar::clock::id(), &Clock { slot: 10, ..Clock::default() });
+        let mut config = TestAccount::new(address(&[AUCTION_CONFIG_SEED]), 0, config_data, program_id);
+        let mut system = TestAccount::new(system_program::id(), 0, vec![], Pubkey::default());
+        let mut game_state_account = TestAccount::new(address(&[GAME_STATE_SEED]), 0, game_state_data, program_id);
+        let mut bid_index = TestAccount::new(bid_index_key, 0, vec![0; bid_index::bid_index_space(4)], program_id);
+        let mut rent_account = TestAccount::sysvar(sysvar::rent::id(), &rent);
+
+        // Anyone can send lamports to the next page address before the bid opens it
+        let mut page = TestAccount::new(address(&[AUCTION_LIST_SEED, &0u64.to_le_bytes()]), 1, vec![0; page_space], system_program::id());
+
+        let accounts = [
+            bidder.info(true),
+            page.info(false),
+            treasury.info(false),
+            auction_info_account.info(false),
+            clock.info(false),
+            config.info(false),
+            system.info(false),
+            game_state_account.info(false),
+            bid_index.info(false),
+            rent_account.info(false),
+        ];
+        Processor::process_bid(&accounts, amount, &program_id).unwrap();
+        drop(accounts);
+
+        // Page is topped up to rent exemption and taken over by the program
+        assert_eq!(page.owner(), program_id);
+        assert_eq!(page.lamports, rent.minimum_balance(page_space));
+        assert_eq!(bidder.lamports, 10_000_000_000 - amount - (rent.minimum_balance(page_space) - 1));
+        assert_eq!(treasury.lamports, amount);
+        let bid_entry = BidEntry::unpack_unchecked(&page.data[..BidEntry::LEN]).unwrap();
+        assert_eq!(bid_entry.bidder_pubkey, bidder.key);
+        assert_eq!(bid_entry.amount_lamports, amount);
+        let auction_info = AuctionInfo::unpack_unchecked(&auction_info_account.data).unwrap();
+        assert_eq!(auction_info.page_count, 1);
+        assert_eq!(auction_info.bid_count, 1);
+    }
+
+    #[test]
+    fn test_purge_square() {
+        let mut auction_info = auction_info(100);
+        auction_info.squares_minted = 3;
+        let mut game_state = GameState::unpack_unchecked(&[0; GameState::LEN]).unwrap();
+        game_state.game_phase = GamePhase::Showdown;
+        game_state.prize_pool = 900;
+        game_state.team_square_counts[0] = 1;
+        game_state.team_square_counts[1] = 1;
+        game_state.team_square_counts[2] = 1;
+
+        Processor::purge_square(&mut game_state, &auction_info, 0).unwrap();
+        assert_eq!(game_state.game_phase, GamePhase::Showdown);
+
+        // Purging down to one team ends the game, the remaining square takes the prize
+        Processor::purge_square(&mut game_state, &auction_info, 1).unwrap();
+        assert_eq!(game_state.game_phase, GamePhase::GameOver);
+        assert_eq!(game_state.winning_team, 2);
+        assert_eq!(game_state.prize_share, 900);
+
+        // Never purges the last square in the game
+        game_state.game_phase = GamePhase::Showdown;
+        assert_eq!(
+            Processor::purge_square(&mut game_state, &auction_info, 2),
+            Err(SixtyFourGameError::LastSquareInGame.into())
         );
-        let instruction_data: Vec<u8> = Vec::new();
+        assert_eq!(game_state.squares_purged, 2);
+        assert_eq!(game_state.team_square_counts[2], 1);
+    }
+
+    #[test]
+    fn test_forfeit_unrevealed() {
+        let mut auction_config = auction_config(2);
+        auction_config.reveal_slots = 10;
+        auction_config.forfeit_unrevealed = true;
+        let mut auction_info = auction_info(100);
+        auction_info.sealed_deposits = 500;
+        let mut game_state = GameState::unpack_unchecked(&[0; GameState::LEN]).unwrap();
+        game_state.game_phase = GamePhase::Reveal;
+
+        Processor::update_game_phase(&mut game_state, &auction_info, &auction_config, 110);
+        assert_eq!(game_state.game_phase, GamePhase::Reveal);
+        assert_eq!(game_state.prize_pool, 0);
+
+        // Deposits still sealed join the prize once the reveal ends, before any mint
+        Processor::update_game_phase(&mut game_state, &auction_info, &auction_config, 111);
+        assert_eq!(game_state.game_phase, GamePhase::Minting);
+        assert_eq!(game_state.prize_pool, 500);
+
+        // Without a square minted there is no prize to forfeit into
+        Processor::start_play(&mut game_state, &auction_info);
+        assert_eq!(game_state.game_phase, GamePhase::Settled);
+        assert_eq!(game_state.prize_pool, 0);
+    }
+
+    #[test]
+    fn test_dutch_auction_end() {
+        let mut auction_config = auction_config(2);
+        auction_config.auction_mode = AuctionMode::Dutch;
+        auction_config.showdown_delay_slots = 1000;
+        let mut auction_info = auction_info(100);
+        auction_info.squares_minted = 2;
+        let mut game_state = GameState::unpack_unchecked(&[0; GameState::LEN]).unwrap();
+        game_state.game_phase = GamePhase::Auction;
+        game_state.team_square_counts[0] = 1;
+        game_state.team_square_counts[1] = 1;
+
+        Processor::update_game_phase(&mut game_state, &auction_info, &auction_config, 100);
+        assert_eq!(game_state.game_phase, GamePhase::Auction);
+
+        // Play starts with the squares sold by auction_end_slot
+        Processor::update_game_phase(&mut game_state, &auction_info, &auction_config, 101);
+        assert_eq!(game_state.game_phase, GamePhase::Play);
+
+        // A single team sold ends the game at once
+        game_state.game_phase = GamePhase::Auction;
+        game_state.prize_pool = 300;
+        game_state.team_square_counts[1] = 0;
+        auction_info.squares_minted = 1;
+        Processor::update_game_phase(&mut game_state, &auction_info, &auction_config, 101);
+        assert_eq!(game_state.game_phase, GamePhase::GameOver);
+        assert_eq!(game_state.prize_share, 300);
+    }
 
-        let accounts = vec![account];
+    #[test]
+    fn test_apply_soft_close() {
+        let mut auction_config = auction_config(2);
+        auction_config.soft_close_slots = 10;
+        auction_config.extension_slots = 5;
+        auction_config.max_auction_end_slot = 112;
+
+        // Winning rank bid inside the soft close window extends the auction
+        let mut auction_info = auction_info(100);
+        Processor::apply_soft_close(&mut auction_info, &auction_config, 3, 95);
+        assert_eq!(auction_info.auction_end_slot, 105);
+
+        // Up to max_auction_end_slot
+        Processor::apply_soft_close(&mut auction_info, &auction_config, 0, 104);
+        assert_eq!(auction_info.auction_end_slot, 110);
+        Processor::apply_soft_close(&mut auction_info, &auction_config, 0, 109);
+        assert_eq!(auction_info.auction_end_slot, 112);
+        Processor::apply_soft_close(&mut auction_info, &auction_config, 0, 111);
+        assert_eq!(auction_info.auction_end_slot, 112);
+    }
 
-        assert_eq!(LittleEndian::read_u64(&accounts[0].data.borrow()), 0);
-        process_instruction(&program_id, &accounts, &instruction_data).unwrap();
-        assert_eq!(LittleEndian::read_u64(&accounts[0].data.borrow()), 1);
-        process_instruction(&program_id, &accounts, &instruction_data).unwrap();
-        assert_eq!(LittleEndian::read_u64(&accounts[0].data.borrow()), 2);
+    #[test]
+    fn test_apply_soft_close_ignored() {
+        let mut auction_config = auction_config(2);
+        auction_config.soft_close_slots = 10;
+        auction_config.extension_slots = 5;
+        auction_config.max_auction_end_slot = 200;
+
+        // Bid before the soft close window
+        let mut auction_info = auction_info(100);
+        Processor::apply_soft_close(&mut auction_info, &auction_config, 0, 90);
+        assert_eq!(auction_info.auction_end_slot, 100);
+
+        // Bid outside the winning ranks
+        Processor::apply_soft_close(&mut auction_info, &auction_config, 4, 95);
+        assert_eq!(auction_info.auction_end_slot, 100);
+
+        // Soft close disabled
+        auction_config.soft_close_slots = 0;
+        Processor::apply_soft_close(&mut auction_info, &auction_config, 0, 100);
+        assert_eq!(auction_info.auction_end_slot, 100);
     }
 }
 
@@ -2,25 +2,139 @@
 use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
 use solana_program::{
     program_pack::{IsInitialized, Pack, Sealed},
+    hash::Hash,
     program_error::ProgramError,
     pubkey::Pubkey,
 };
 
-use crate::{
-    error::SixtyFourGameError,
-};
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BidStatus {
+    Active,
+    Cancelled,
+    Won,
+    Refunded,
+    /// Committed in a sealed bid auction, amount_lamports holds the deposit until RevealBid
+    Sealed,
+}
 
 pub struct BidEntry {
     pub bid_number: u64,
     pub amount_lamports: u64,
     pub bidder_pubkey: Pubkey,
+    pub bid_status: BidStatus,
+    /// Rank the bid finished at, the square it won. Only set once the bid is Won
+    pub winning_rank: u64,
+    /// Breaks amount ties, lower ranks first. Set from the AuctionInfo bid_sequence when
+    /// the bid is placed and again on IncreaseBid unless top ups keep their priority
+    pub priority: u64,
+    /// util::bid_commitment of a sealed bid, unused by open bids
+    pub commitment: Hash,
+}
+
+impl BidEntry {
+    /// (amount_lamports, priority) the auction ranks bids by, see bid_index::compare_bids
+    pub fn ranking_key(&self) -> (u64, u64) {
+        (self.amount_lamports, self.priority)
+    }
+}
+
+/// Most teams a game can be configured with
+pub const MAX_TEAM_COUNT: u64 = 8;
+
+/// How the squares are sold. English ranks bids until auction_end_slot and mints the winners
+/// after, Dutch sells the next square with BuySquare at a price dropping every slot
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AuctionMode {
+    English,
+    Dutch,
+}
+
+pub struct AuctionConfig {
+    pub is_initialized: bool,
+    pub admin_pubkey: Pubkey,
+    pub max_bid_count: u64,
+    pub board_side: u64,
+    pub team_count: u64,
+    pub starting_health: u64,
+    pub showdown_delay_slots: u64,
+    /// A bid entering the winning ranks this many slots before the auction end extends it
+    pub soft_close_slots: u64,
+    /// Slots a soft close bid adds to the auction end
+    pub extension_slots: u64,
+    /// Hard cap on the auction end slot, extensions stop here
+    pub max_auction_end_slot: u64,
+    /// Least amount of any bid
+    pub min_bid_lamports: u64,
+    /// Reserve price of rank 0, a bid has to meet the reserve of the rank it enters at
+    pub reserve_price_lamports: u64,
+    /// Reserve price drop per rank
+    pub reserve_step_lamports: u64,
+    /// Least amount a bid has to beat the last winning rank by once every rank is taken
+    pub min_increment_lamports: u64,
+    /// Account holding the bids sorted by rank, see bid_index
+    pub bid_index_pubkey: Pubkey,
+    /// IncreaseBid keeps the bid's tie priority, otherwise a top up ranks like a new bid
+    pub top_up_keeps_priority: bool,
+    /// Slots after the auction end sealed bids can be revealed in, 0 takes open bids
+    pub reveal_slots: u64,
+    /// Deposits of sealed bids not revealed in time go to the prize, otherwise ClaimRefund returns them
+    pub forfeit_unrevealed: bool,
+    pub auction_mode: AuctionMode,
+    /// Dutch auctions only, price of each square when its sale opens
+    pub dutch_start_price_lamports: u64,
+    /// Dutch auctions only, price drop per slot, the price stops at min_bid_lamports
+    pub dutch_price_step_lamports: u64,
+    /// Mint bids and the prize are paid in, escrowed in the bid escrow program token account.
+    /// The default pubkey keeps bids in native SOL lamports
+    pub bid_mint_pubkey: Pubkey,
+    /// Account holding the GameSquare of every square
+    pub all_game_squares_list_pubkey: Pubkey,
+    /// Account holding the AuctionInfo
+    pub auction_info_pubkey: Pubkey,
+    /// Account holding the ActivePlayer of every square
+    pub active_players_list_pubkey: Pubkey,
+}
+
+impl AuctionConfig {
+    /// Number of game squares minted from the auction, the board is square
+    pub fn max_game_square_count(&self) -> u64 {
+        self.board_side * self.board_side
+    }
+
+    /// Bids are paid in bid_mint_pubkey tokens instead of lamports
+    pub fn is_token_bid(&self) -> bool {
+        self.bid_mint_pubkey != Pubkey::default()
+    }
+
+    /// Bids are committed with CommitBid and revealed after the auction end
+    pub fn is_sealed_bid(&self) -> bool {
+        self.reveal_slots > 0
+    }
+
+    /// Dutch auction price of the square on sale since price_start_slot
+    pub fn dutch_price(&self, price_start_slot: u64, current_slot: u64) -> u64 {
+        let drop = current_slot.saturating_sub(price_start_slot).saturating_mul(self.dutch_price_step_lamports);
+        self.dutch_start_price_lamports.saturating_sub(drop).max(self.min_bid_lamports)
+    }
+
+    /// Reserve price of a rank, drops by reserve_step_lamports per rank
+    pub fn reserve_price(&self, rank: u64) -> u64 {
+        self.reserve_price_lamports.saturating_sub(rank.saturating_mul(self.reserve_step_lamports))
+    }
 }
 
 pub struct AuctionInfo {
     pub bid_count: u64,
     pub squares_minted: u64,
     pub auction_end_slot: u64,
-    pub auction_enabled: bool
+    /// Auction list pages created so far, Bid creates the next page once the last one is full
+    pub page_count: u64,
+    /// Next bid priority, counts every Bid and IncreaseBid that takes a new priority
+    pub bid_sequence: u64,
+    /// Deposits of sealed bids not revealed or cancelled yet
+    pub sealed_deposits: u64,
+    /// Dutch auctions only, slot the sale of the next square opened at
+    pub price_start_slot: u64,
 }
 
 pub struct GameSquare {
@@ -28,34 +142,124 @@ pub struct GameSquare {
     pub team_number: u64,
     pub health_number: u64,
     pub mint_pubkey: Pubkey,
+    pub purged: bool,
+    pub prize_claimed: bool,
+}
+
+/// Phases of a game in order. Auction takes bids until auction_end_slot, Reveal follows for
+/// reveal_slots in sealed bid auctions, Minting mints the winning bids, Play starts once
+/// every square is minted, Showdown starts showdown_delay_slots after the auction end and
+/// lets anyone purge squares that are not in play, GameOver lets the winning team claim the
+/// prize and Settled follows once every prize is claimed
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GamePhase {
+    Uninitialized,
+    Auction,
+    Reveal,
+    Minting,
+    Play,
+    Showdown,
+    GameOver,
+    Settled,
+}
+
+pub struct GameState {
+    pub game_phase: GamePhase,
+    /// Team that owns every remaining square, set at GameOver
+    pub winning_team: u64,
+    pub squares_purged: u64,
+    /// Sum of the winning bids, what the winning team splits
+    pub prize_pool: u64,
+    /// Lamports paid per winning square, set at GameOver
+    pub prize_share: u64,
+    pub prizes_claimed: u64,
+    /// Squares left in the game per team, updated on mints, captures and purges
+    pub team_square_counts: [u64; MAX_TEAM_COUNT as usize],
+}
+
+/// Inactive squares are not deposited, Active squares are deposited by their owner and
+/// Captured squares are deposited and owned by the player who defeated them
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ActivePlayerStatus {
+    Inactive,
+    Active,
+    Captured,
 }
 
 pub struct ActivePlayer {
     pub game_square_number: u64,
+    pub status: ActivePlayerStatus,
     pub owner_pubkey: Pubkey,
     pub program_token_account_pubkey: Pubkey,
+    /// Attack committed by this square, waiting for ResolveAttack
+    pub attack_pending: bool,
+    pub attack_to_square: u64,
+    pub attack_amount: u64,
+    pub attack_commit_slot: u64,
+    pub attack_commitment: Hash,
+}
+
+impl ActivePlayer {
+    /// True if the square's NFT is deposited and the square can attack or get attacked
+    pub fn is_active(&self) -> bool {
+        self.status != ActivePlayerStatus::Inactive
+    }
+
+    /// Drops the square's pending attack, a new owner does not know the attack secret
+    pub fn clear_attack(&mut self) {
+        self.attack_pending = false;
+        self.attack_to_square = 0;
+        self.attack_amount = 0;
+        self.attack_commit_slot = 0;
+        self.attack_commitment = Hash::default();
+    }
 }
 
 impl Sealed for BidEntry {}
+impl Sealed for AuctionConfig {}
 impl Sealed for AuctionInfo {}
 impl Sealed for GameSquare {}
 impl Sealed for ActivePlayer {}
+impl Sealed for GameState {}
 
 impl Pack for ActivePlayer {
-    const LEN: usize = 72;
+    const LEN: usize = 130;
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
 
         let src = array_ref![src, 0, ActivePlayer::LEN];
         let (
             game_square_number,
+            status,
             owner_pubkey,
             program_token_account_pubkey,
-        ) = array_refs![src, 8, 32, 32];
+            attack_pending,
+            attack_to_square,
+            attack_amount,
+            attack_commit_slot,
+            attack_commitment,
+        ) = array_refs![src, 8, 1, 32, 32, 1, 8, 8, 8, 32];
+        let status = match status {
+            [0] => ActivePlayerStatus::Inactive,
+            [1] => ActivePlayerStatus::Active,
+            [2] => ActivePlayerStatus::Captured,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        let attack_pending = match attack_pending {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
 
         Ok(ActivePlayer {
             game_square_number: u64::from_le_bytes(*game_square_number),
+            status,
             owner_pubkey: Pubkey::new_from_array(*owner_pubkey),
             program_token_account_pubkey: Pubkey::new_from_array(*program_token_account_pubkey),
+            attack_pending,
+            attack_to_square: u64::from_le_bytes(*attack_to_square),
+            attack_amount: u64::from_le_bytes(*attack_amount),
+            attack_commit_slot: u64::from_le_bytes(*attack_commit_slot),
+            attack_commitment: Hash::new_from_array(*attack_commitment),
         })
     }
 
@@ -63,24 +267,42 @@ impl Pack for ActivePlayer {
         let dst = array_mut_ref![dst, 0, ActivePlayer::LEN];
         let (
             game_square_number_dst,
+            status_dst,
             owner_pubkey_dst,
             program_token_account_pubkey_dst,
-        ) = mut_array_refs![dst, 8, 32, 32];
+            attack_pending_dst,
+            attack_to_square_dst,
+            attack_amount_dst,
+            attack_commit_slot_dst,
+            attack_commitment_dst,
+        ) = mut_array_refs![dst, 8, 1, 32, 32, 1, 8, 8, 8, 32];
 
         let ActivePlayer {
             game_square_number,
+            status,
             owner_pubkey,
             program_token_account_pubkey,
+            attack_pending,
+            attack_to_square,
+            attack_amount,
+            attack_commit_slot,
+            attack_commitment,
         } = self;
 
         *game_square_number_dst = game_square_number.to_le_bytes();
+        status_dst[0] = *status as u8;
         owner_pubkey_dst.copy_from_slice(owner_pubkey.as_ref());
         program_token_account_pubkey_dst.copy_from_slice(program_token_account_pubkey.as_ref());
+        attack_pending_dst[0] = *attack_pending as u8;
+        *attack_to_square_dst = attack_to_square.to_le_bytes();
+        *attack_amount_dst = attack_amount.to_le_bytes();
+        *attack_commit_slot_dst = attack_commit_slot.to_le_bytes();
+        attack_commitment_dst.copy_from_slice(attack_commitment.as_ref());
     }
 }
 
 impl Pack for GameSquare {
-    const LEN: usize = 56;
+    const LEN: usize = 58;
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
 
         let src = array_ref![src, 0, GameSquare::LEN];
@@ -89,13 +311,27 @@ impl Pack for GameSquare {
             team_number,
             health_number,
             mint_pubkey,
-        ) = array_refs![src, 8, 8, 8, 32];
+            purged,
+            prize_claimed,
+        ) = array_refs![src, 8, 8, 8, 32, 1, 1];
+        let purged = match purged {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        let prize_claimed = match prize_claimed {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
 
         Ok(GameSquare {
             game_square_number: u64::from_le_bytes(*game_square_number),
             team_number: u64::from_le_bytes(*team_number),
             health_number: u64::from_le_bytes(*health_number),
             mint_pubkey: Pubkey::new_from_array(*mint_pubkey),
+            purged,
+            prize_claimed,
         })
     }
 
@@ -106,24 +342,279 @@ impl Pack for GameSquare {
             team_number_dst,
             health_number_dst,
             mint_pubkey_dst,
-        ) = mut_array_refs![dst, 8, 8, 8, 32];
+            purged_dst,
+            prize_claimed_dst,
+        ) = mut_array_refs![dst, 8, 8, 8, 32, 1, 1];
 
         let GameSquare {
             game_square_number,
             team_number,
             health_number,
             mint_pubkey,
+            purged,
+            prize_claimed,
         } = self;
 
         *game_square_number_dst = game_square_number.to_le_bytes();
         *team_number_dst = team_number.to_le_bytes();
         *health_number_dst = health_number.to_le_bytes();
         mint_pubkey_dst.copy_from_slice(mint_pubkey.as_ref());
+        purged_dst[0] = *purged as u8;
+        prize_claimed_dst[0] = *prize_claimed as u8;
+    }
+}
+
+impl Pack for GameState {
+    const LEN: usize = 105;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+
+        let src = array_ref![src, 0, GameState::LEN];
+        let (
+            game_phase,
+            winning_team,
+            squares_purged,
+            prize_pool,
+            prize_share,
+            prizes_claimed,
+            team_square_counts_src,
+        ) = array_refs![src, 1, 8, 8, 8, 8, 8, 8 * MAX_TEAM_COUNT as usize];
+        let game_phase = match game_phase {
+            [0] => GamePhase::Uninitialized,
+            [1] => GamePhase::Auction,
+            [2] => GamePhase::Reveal,
+            [3] => GamePhase::Minting,
+            [4] => GamePhase::Play,
+            [5] => GamePhase::Showdown,
+            [6] => GamePhase::GameOver,
+            [7] => GamePhase::Settled,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        let mut team_square_counts = [0u64; MAX_TEAM_COUNT as usize];
+        for (count, count_src) in team_square_counts.iter_mut().zip(team_square_counts_src.chunks_exact(8)) {
+            *count = u64::from_le_bytes(*array_ref![count_src, 0, 8]);
+        }
+
+        Ok(GameState {
+            game_phase,
+            winning_team: u64::from_le_bytes(*winning_team),
+            squares_purged: u64::from_le_bytes(*squares_purged),
+            prize_pool: u64::from_le_bytes(*prize_pool),
+            prize_share: u64::from_le_bytes(*prize_share),
+            prizes_claimed: u64::from_le_bytes(*prizes_claimed),
+            team_square_counts,
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, GameState::LEN];
+        let (
+            game_phase_dst,
+            winning_team_dst,
+            squares_purged_dst,
+            prize_pool_dst,
+            prize_share_dst,
+            prizes_claimed_dst,
+            team_square_counts_dst,
+        ) = mut_array_refs![dst, 1, 8, 8, 8, 8, 8, 8 * MAX_TEAM_COUNT as usize];
+
+        let GameState {
+            game_phase,
+            winning_team,
+            squares_purged,
+            prize_pool,
+            prize_share,
+            prizes_claimed,
+            team_square_counts,
+        } = self;
+
+        game_phase_dst[0] = *game_phase as u8;
+        *winning_team_dst = winning_team.to_le_bytes();
+        *squares_purged_dst = squares_purged.to_le_bytes();
+        *prize_pool_dst = prize_pool.to_le_bytes();
+        *prize_share_dst = prize_share.to_le_bytes();
+        *prizes_claimed_dst = prizes_claimed.to_le_bytes();
+        for (count, count_dst) in team_square_counts.iter().zip(team_square_counts_dst.chunks_exact_mut(8)) {
+            count_dst.copy_from_slice(&count.to_le_bytes());
+        }
+    }
+}
+
+impl IsInitialized for AuctionConfig {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for AuctionConfig {
+    const LEN: usize = 316;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+
+        let src = array_ref![src, 0, AuctionConfig::LEN];
+        let (
+            is_initialized,
+            admin_pubkey,
+            max_bid_count,
+            board_side,
+            team_count,
+            starting_health,
+            showdown_delay_slots,
+            soft_close_slots,
+            extension_slots,
+            max_auction_end_slot,
+            min_bid_lamports,
+            reserve_price_lamports,
+            reserve_step_lamports,
+            min_increment_lamports,
+            bid_index_pubkey,
+            top_up_keeps_priority,
+            reveal_slots,
+            forfeit_unrevealed,
+            auction_mode,
+            dutch_start_price_lamports,
+            dutch_price_step_lamports,
+            bid_mint_pubkey,
+            all_game_squares_list_pubkey,
+            auction_info_pubkey,
+            active_players_list_pubkey,
+        ) = array_refs![src, 1, 32, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 32, 1, 8, 1, 1, 8, 8, 32, 32, 32, 32];
+        let is_initialized = match is_initialized {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        let top_up_keeps_priority = match top_up_keeps_priority {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        let forfeit_unrevealed = match forfeit_unrevealed {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        let auction_mode = match auction_mode {
+            [0] => AuctionMode::English,
+            [1] => AuctionMode::Dutch,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+
+        Ok(AuctionConfig {
+            is_initialized,
+            admin_pubkey: Pubkey::new_from_array(*admin_pubkey),
+            max_bid_count: u64::from_le_bytes(*max_bid_count),
+            board_side: u64::from_le_bytes(*board_side),
+            team_count: u64::from_le_bytes(*team_count),
+            starting_health: u64::from_le_bytes(*starting_health),
+            showdown_delay_slots: u64::from_le_bytes(*showdown_delay_slots),
+            soft_close_slots: u64::from_le_bytes(*soft_close_slots),
+            extension_slots: u64::from_le_bytes(*extension_slots),
+            max_auction_end_slot: u64::from_le_bytes(*max_auction_end_slot),
+            min_bid_lamports: u64::from_le_bytes(*min_bid_lamports),
+            reserve_price_lamports: u64::from_le_bytes(*reserve_price_lamports),
+            reserve_step_lamports: u64::from_le_bytes(*reserve_step_lamports),
+            min_increment_lamports: u64::from_le_bytes(*min_increment_lamports),
+            bid_index_pubkey: Pubkey::new_from_array(*bid_index_pubkey),
+            top_up_keeps_priority,
+            reveal_slots: u64::from_le_bytes(*reveal_slots),
+            forfeit_unrevealed,
+            auction_mode,
+            dutch_start_price_lamports: u64::from_le_bytes(*dutch_start_price_lamports),
+            dutch_price_step_lamports: u64::from_le_bytes(*dutch_price_step_lamports),
+            bid_mint_pubkey: Pubkey::new_from_array(*bid_mint_pubkey),
+            all_game_squares_list_pubkey: Pubkey::new_from_array(*all_game_squares_list_pubkey),
+            auction_info_pubkey: Pubkey::new_from_array(*auction_info_pubkey),
+            active_players_list_pubkey: Pubkey::new_from_array(*active_players_list_pubkey),
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, AuctionConfig::LEN];
+        let (
+            is_initialized_dst,
+            admin_pubkey_dst,
+            max_bid_count_dst,
+            board_side_dst,
+            team_count_dst,
+            starting_health_dst,
+            showdown_delay_slots_dst,
+            soft_close_slots_dst,
+            extension_slots_dst,
+            max_auction_end_slot_dst,
+            min_bid_lamports_dst,
+            reserve_price_lamports_dst,
+            reserve_step_lamports_dst,
+            min_increment_lamports_dst,
+            bid_index_pubkey_dst,
+            top_up_keeps_priority_dst,
+            reveal_slots_dst,
+            forfeit_unrevealed_dst,
+            auction_mode_dst,
+            dutch_start_price_lamports_dst,
+            dutch_price_step_lamports_dst,
+            bid_mint_pubkey_dst,
+            all_game_squares_list_pubkey_dst,
+            auction_info_pubkey_dst,
+            active_players_list_pubkey_dst,
+        ) = mut_array_refs![dst, 1, 32, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 32, 1, 8, 1, 1, 8, 8, 32, 32, 32, 32];
+
+        let AuctionConfig {
+            is_initialized,
+            admin_pubkey,
+            max_bid_count,
+            board_side,
+            team_count,
+            starting_health,
+            showdown_delay_slots,
+            soft_close_slots,
+            extension_slots,
+            max_auction_end_slot,
+            min_bid_lamports,
+            reserve_price_lamports,
+            reserve_step_lamports,
+            min_increment_lamports,
+            bid_index_pubkey,
+            top_up_keeps_priority,
+            reveal_slots,
+            forfeit_unrevealed,
+            auction_mode,
+            dutch_start_price_lamports,
+            dutch_price_step_lamports,
+            bid_mint_pubkey,
+            all_game_squares_list_pubkey,
+            auction_info_pubkey,
+            active_players_list_pubkey,
+        } = self;
+
+        is_initialized_dst[0] = *is_initialized as u8;
+        admin_pubkey_dst.copy_from_slice(admin_pubkey.as_ref());
+        *max_bid_count_dst = max_bid_count.to_le_bytes();
+        *board_side_dst = board_side.to_le_bytes();
+        *team_count_dst = team_count.to_le_bytes();
+        *starting_health_dst = starting_health.to_le_bytes();
+        *showdown_delay_slots_dst = showdown_delay_slots.to_le_bytes();
+        *soft_close_slots_dst = soft_close_slots.to_le_bytes();
+        *extension_slots_dst = extension_slots.to_le_bytes();
+        *max_auction_end_slot_dst = max_auction_end_slot.to_le_bytes();
+        *min_bid_lamports_dst = min_bid_lamports.to_le_bytes();
+        *reserve_price_lamports_dst = reserve_price_lamports.to_le_bytes();
+        *reserve_step_lamports_dst = reserve_step_lamports.to_le_bytes();
+        *min_increment_lamports_dst = min_increment_lamports.to_le_bytes();
+        bid_index_pubkey_dst.copy_from_slice(bid_index_pubkey.as_ref());
+        top_up_keeps_priority_dst[0] = *top_up_keeps_priority as u8;
+        *reveal_slots_dst = reveal_slots.to_le_bytes();
+        forfeit_unrevealed_dst[0] = *forfeit_unrevealed as u8;
+        auction_mode_dst[0] = *auction_mode as u8;
+        *dutch_start_price_lamports_dst = dutch_start_price_lamports.to_le_bytes();
+        *dutch_price_step_lamports_dst = dutch_price_step_lamports.to_le_bytes();
+        bid_mint_pubkey_dst.copy_from_slice(bid_mint_pubkey.as_ref());
+        all_game_squares_list_pubkey_dst.copy_from_slice(all_game_squares_list_pubkey.as_ref());
+        auction_info_pubkey_dst.copy_from_slice(auction_info_pubkey.as_ref());
+        active_players_list_pubkey_dst.copy_from_slice(active_players_list_pubkey.as_ref());
     }
 }
 
 impl Pack for AuctionInfo {
-    const LEN: usize = 25;
+    const LEN: usize = 56;
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
 
         let src = array_ref![src, 0, AuctionInfo::LEN];
@@ -131,19 +622,20 @@ impl Pack for AuctionInfo {
             bid_count,
             squares_minted,
             auction_end_slot,
-            auction_enabled
-        ) = array_refs![src, 8, 8, 8, 1];
-        let auction_enabled = match auction_enabled {
-            [0] => false,
-            [1] => true,
-            _ => return Err(ProgramError::InvalidAccountData),
-        };
+            page_count,
+            bid_sequence,
+            sealed_deposits,
+            price_start_slot,
+        ) = array_refs![src, 8, 8, 8, 8, 8, 8, 8];
 
         Ok(AuctionInfo {
             bid_count: u64::from_le_bytes(*bid_count),
             squares_minted: u64::from_le_bytes(*squares_minted),
             auction_end_slot: u64::from_le_bytes(*auction_end_slot),
-            auction_enabled: auction_enabled,
+            page_count: u64::from_le_bytes(*page_count),
+            bid_sequence: u64::from_le_bytes(*bid_sequence),
+            sealed_deposits: u64::from_le_bytes(*sealed_deposits),
+            price_start_slot: u64::from_le_bytes(*price_start_slot),
         })
     }
 
@@ -153,25 +645,34 @@ impl Pack for AuctionInfo {
             bid_count_dst,
             squares_minted_dst,
             auction_end_slot_dst,
-            auction_enabled_dst,
-        ) = mut_array_refs![dst, 8, 8, 8, 1];
+            page_count_dst,
+            bid_sequence_dst,
+            sealed_deposits_dst,
+            price_start_slot_dst,
+        ) = mut_array_refs![dst, 8, 8, 8, 8, 8, 8, 8];
 
         let AuctionInfo {
             bid_count,
             squares_minted,
             auction_end_slot,
-            auction_enabled
+            page_count,
+            bid_sequence,
+            sealed_deposits,
+            price_start_slot,
         } = self;
 
         *bid_count_dst = bid_count.to_le_bytes();
         *squares_minted_dst = squares_minted.to_le_bytes();
         *auction_end_slot_dst = auction_end_slot.to_le_bytes();
-        auction_enabled_dst[0] = *auction_enabled as u8;
+        *page_count_dst = page_count.to_le_bytes();
+        *bid_sequence_dst = bid_sequence.to_le_bytes();
+        *sealed_deposits_dst = sealed_deposits.to_le_bytes();
+        *price_start_slot_dst = price_start_slot.to_le_bytes();
     }
 }
 
 impl Pack for BidEntry {
-    const LEN: usize = 48;
+    const LEN: usize = 97;
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
 
         let src = array_ref![src, 0, BidEntry::LEN];
@@ -179,12 +680,28 @@ impl Pack for BidEntry {
             bid_number,
             amount_lamports,
             bidder_pubkey,
-        ) = array_refs![src, 8, 8, 32];
+            bid_status,
+            winning_rank,
+            priority,
+            commitment,
+        ) = array_refs![src, 8, 8, 32, 1, 8, 8, 32];
+        let bid_status = match bid_status {
+            [0] => BidStatus::Active,
+            [1] => BidStatus::Cancelled,
+            [2] => BidStatus::Won,
+            [3] => BidStatus::Refunded,
+            [4] => BidStatus::Sealed,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
 
         Ok(BidEntry {
             bid_number: u64::from_le_bytes(*bid_number),
             amount_lamports: u64::from_le_bytes(*amount_lamports),
             bidder_pubkey: Pubkey::new_from_array(*bidder_pubkey),
+            bid_status,
+            winning_rank: u64::from_le_bytes(*winning_rank),
+            priority: u64::from_le_bytes(*priority),
+            commitment: Hash::new_from_array(*commitment),
         })
     }
 
@@ -194,16 +711,48 @@ impl Pack for BidEntry {
             bid_number_dst,
             amount_lamports_dst,
             bidder_pubkey_dst,
-        ) = mut_array_refs![dst, 8, 8, 32];
+            bid_status_dst,
+            winning_rank_dst,
+            priority_dst,
+            commitment_dst,
+        ) = mut_array_refs![dst, 8, 8, 32, 1, 8, 8, 32];
 
         let BidEntry {
             bid_number,
             amount_lamports,
             bidder_pubkey,
+            bid_status,
+            winning_rank,
+            priority,
+            commitment,
         } = self;
 
         *bid_number_dst = bid_number.to_le_bytes();
         *amount_lamports_dst = amount_lamports.to_le_bytes();
         bidder_pubkey_dst.copy_from_slice(bidder_pubkey.as_ref());
+        bid_status_dst[0] = *bid_status as u8;
+        *winning_rank_dst = winning_rank.to_le_bytes();
+        *priority_dst = priority.to_le_bytes();
+        commitment_dst.copy_from_slice(commitment.as_ref());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_dutch_price() {
+        let mut auction_config = AuctionConfig::unpack_unchecked(&[0; AuctionConfig::LEN]).unwrap();
+        auction_config.min_bid_lamports = 100;
+        auction_config.dutch_start_price_lamports = 1000;
+        auction_config.dutch_price_step_lamports = 30;
+        assert_eq!(auction_config.dutch_price(50, 50), 1000);
+        assert_eq!(auction_config.dutch_price(50, 60), 700);
+        // Price stops at the minimum bid
+        assert_eq!(auction_config.dutch_price(50, 80), 100);
+        assert_eq!(auction_config.dutch_price(50, u64::MAX), 100);
+        // Sale opened after the current slot has not dropped yet
+        assert_eq!(auction_config.dutch_price(50, 40), 1000);
     }
 }
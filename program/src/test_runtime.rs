@@ -0,0 +1,151 @@
+//! Runtime stand-in for tests
+//!
+//! TestAccount keeps the account data the AccountInfos handed to the processor
+//! borrow. Installed as the syscall stubs, the system program stand-in runs the
+//! system instructions the program invokes against the AccountInfos passed to
+//! the invoke, with the runtime rules the program depends on. Test accounts
+//! cannot be resized, so an account has to start with the data length the
+//! program allocates, and AccountInfo only borrows its owner, so owners the
+//! program assigns are kept aside and read with TestAccount::owner.
+
+use solana_sdk::{
+    account_info::AccountInfo,
+    clock::Epoch,
+    entrypoint::ProgramResult,
+    instruction::Instruction,
+    program_error::ProgramError,
+    program_stubs::{set_syscall_stubs, SyscallStubs},
+    program_utils::limited_deserialize,
+    pubkey::Pubkey,
+    system_instruction::SystemInstruction,
+    system_program,
+    sysvar::Sysvar,
+};
+use std::{cell::RefCell, collections::HashMap};
+
+thread_local! {
+    static ASSIGNED_OWNERS: RefCell<HashMap<Pubkey, Pubkey>> = RefCell::new(HashMap::new());
+}
+
+fn current_owner(key: &Pubkey, owner: &Pubkey) -> Pubkey {
+    ASSIGNED_OWNERS.with(|owners| *owners.borrow().get(key).unwrap_or(owner))
+}
+
+pub struct TestAccount {
+    pub key: Pubkey,
+    pub lamports: u64,
+    pub data: Vec<u8>,
+    pub owner: Pubkey,
+}
+
+impl TestAccount {
+    pub fn new(key: Pubkey, lamports: u64, data: Vec<u8>, owner: Pubkey) -> Self {
+        Self { key, lamports, data, owner }
+    }
+
+    /// Sysvar account holding `sysvar` at its sysvar address `key`
+    pub fn sysvar<S: Sysvar>(key: Pubkey, sysvar: &S) -> Self {
+        let mut account = Self::new(key, 1, vec![0; S::size_of()], solana_sdk::sysvar::id());
+        sysvar.to_account_info(&mut account.info(false)).unwrap();
+        account
+    }
+
+    /// Owner including any assigned by the program
+    pub fn owner(&self) -> Pubkey {
+        current_owner(&self.key, &self.owner)
+    }
+
+    pub fn info(&mut self, is_signer: bool) -> AccountInfo<'_> {
+        AccountInfo::new(
+            &self.key,
+            is_signer,
+            true,
+            &mut self.lamports,
+            &mut self.data,
+            &self.owner,
+            false,
+            Epoch::default(),
+        )
+    }
+}
+
+struct SystemProgramStub {
+    program_id: Pubkey,
+}
+
+/// Runs the system instructions the program invokes for the rest of the test binary,
+/// returns the program id the program address signers are derived from
+pub fn install() -> Pubkey {
+    let program_id = Pubkey::new_from_array([64; 32]);
+    set_syscall_stubs(Box::new(SystemProgramStub { program_id }));
+    program_id
+}
+
+fn transfer(from: &AccountInfo, to: &AccountInfo, lamports: u64) -> ProgramResult {
+    if from.lamports() < lamports {
+        return Err(ProgramError::InsufficientFunds);
+    }
+    **from.lamports.borrow_mut() -= lamports;
+    **to.lamports.borrow_mut() += lamports;
+    Ok(())
+}
+
+fn allocate(account: &AccountInfo, space: u64) -> ProgramResult {
+    if current_owner(account.key, account.owner) != system_program::id() || account.data_len() as u64 != space {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok(())
+}
+
+fn assign(account: &AccountInfo, owner: &Pubkey) -> ProgramResult {
+    if current_owner(account.key, account.owner) != system_program::id() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    ASSIGNED_OWNERS.with(|owners| owners.borrow_mut().insert(*account.key, *owner));
+    Ok(())
+}
+
+impl SyscallStubs for SystemProgramStub {
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        if instruction.program_id != system_program::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let mut signers = Vec::new();
+        for seeds in signers_seeds {
+            signers.push(Pubkey::create_program_address(seeds, &self.program_id)?);
+        }
+        let account = |index: usize| -> Result<&AccountInfo, ProgramError> {
+            let meta = instruction.accounts.get(index).ok_or(ProgramError::NotEnoughAccountKeys)?;
+            let account = account_infos
+                .iter()
+                .find(|account| *account.key == meta.pubkey)
+                .ok_or(ProgramError::NotEnoughAccountKeys)?;
+            if meta.is_signer && !account.is_signer && !signers.contains(account.key) {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            Ok(account)
+        };
+
+        match limited_deserialize(&instruction.data).map_err(|_| ProgramError::InvalidInstructionData)? {
+            SystemInstruction::CreateAccount { lamports, space, owner } => {
+                let (from, to) = (account(0)?, account(1)?);
+                // Runtime refuses to create an account that already holds lamports
+                if to.lamports() > 0 {
+                    return Err(ProgramError::AccountAlreadyInitialized);
+                }
+                transfer(from, to, lamports)?;
+                allocate(to, space)?;
+                assign(to, &owner)
+            }
+            SystemInstruction::Transfer { lamports } => transfer(account(0)?, account(1)?, lamports),
+            SystemInstruction::Allocate { space } => allocate(account(0)?, space),
+            SystemInstruction::Assign { owner } => assign(account(0)?, &owner),
+            _ => Err(ProgramError::InvalidInstructionData),
+        }
+    }
+}
@@ -2,56 +2,422 @@
 use byteorder::{ByteOrder, BigEndian, LittleEndian};
 use solana_sdk::{
     account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
     info,
-    hash::{Hash, HASH_BYTES},
+    msg,
+    hash::{hashv, Hash, HASH_BYTES},
     program_error::ProgramError,
     pubkey::Pubkey,
+    system_instruction,
     sysvar::{
-        clock::Clock, slot_hashes::SlotHashes, Sysvar,
+        clock::Clock, rent::Rent, slot_hashes::SlotHashes, Sysvar,
     },
 };
 
 use crate::{
     error::SixtyFourGameError,
+    state::{AuctionConfig, BidEntry},
 };
 
 use std::convert::TryInto;
 
-use solana_sdk::program::invoke_signed;
+use solana_sdk::program::{invoke, invoke_signed};
 use spl_token::{instruction};
 use solana_sdk::program_pack::Pack as TokenPack;
 use spl_token::state::{Account as TokenAccount, Mint};
 
-use std::hash::{Hash as StdHash, Hasher};
-use std::collections::hash_map::DefaultHasher;
-
 use num_derive::FromPrimitive;
 use solana_sdk::{decode_error::DecodeError};
 use thiserror::Error;
 
-pub fn hash_value<T>(obj: T) -> u64
-    where
-        T: StdHash,
-{
-    let mut hasher = DefaultHasher::new();
-    obj.hash(&mut hasher);
-    hasher.finish()
+/// Slots between an Attack commit and the slot whose hash resolves it
+pub const ATTACK_SLOT_DELAY: u64 = 2;
+
+/// Slots after the commit the attacker has to reveal the secret, afterwards anyone can
+/// resolve the attack as a miss. Stays well inside the 512 slots SlotHashes keeps
+pub const ATTACK_REVEAL_WINDOW: u64 = 300;
+
+/// Commitment the attacker sends with Attack, sha256 of the secret revealed in ResolveAttack
+pub fn attack_commitment(secret: &[u8; 32]) -> Hash {
+    hashv(&[secret])
+}
+
+/// Commitment a sealed bidder sends with CommitBid, sha256 of the amount as a little endian
+/// u64 and the salt revealed in RevealBid
+pub fn bid_commitment(amount: u64, salt: &[u8; 32]) -> Hash {
+    hashv(&[&amount.to_le_bytes(), salt])
+}
+
+/// Roll from 1 to 100, the first 8 bytes of sha256(secret, slot hash, from square, to square)
+/// as a little endian u64 mod 100 plus 1, so anyone can check it off-chain
+pub fn attack_roll(secret: &[u8; 32], slot_hash: &Hash, from_square: u64, to_square: u64) -> u64 {
+    let hash = hashv(&[
+        secret,
+        slot_hash.as_ref(),
+        &from_square.to_le_bytes(),
+        &to_square.to_le_bytes(),
+    ]);
+    let value = u64::from_le_bytes(hash.as_ref()[..8].try_into().unwrap());
+    (value % 100) + 1
+}
+
+/// Finds the first slot at or after `slot` in SlotHashes sysvar data and returns it with its
+/// hash, skipped slots have no entry. None if the sysvar does not reach back to `slot`
+pub fn find_slot_hash(data: &[u8], slot: u64) -> Option<(u64, Hash)> {
+    let entry_len = 8 + HASH_BYTES;
+    let count = u64::from_le_bytes(data.get(..8)?.try_into().ok()?) as usize;
+
+    // Entries are newest first
+    let mut found = None;
+    for i in 0..count {
+        let entry = data.get(8 + i * entry_len..8 + (i + 1) * entry_len)?;
+        let entry_slot = u64::from_le_bytes(entry[..8].try_into().ok()?);
+        if entry_slot < slot {
+            return found;
+        }
+        found = Some((entry_slot, Hash::new(&entry[8..])));
+    }
+
+    // Oldest entry is after `slot`, an expired entry may have been first
+    found.filter(|(found_slot, _)| *found_slot == slot)
+}
+
+/// Seed of the AuctionConfig program address
+pub const AUCTION_CONFIG_SEED: &[u8] = b"config";
+
+/// Confirms the account is the program's AuctionConfig address, returns its bump seed
+pub fn check_auction_config_account(program_id: &Pubkey, account: &AccountInfo) -> Result<u8, ProgramError> {
+    let (address, bump_seed) = Pubkey::find_program_address(&[AUCTION_CONFIG_SEED], program_id);
+    if address != *account.key {
+        msg!("Auction config account does not match the program address");
+        return Err(SixtyFourGameError::InvalidAuctionConfigAccount.into());
+    }
+    Ok(bump_seed)
+}
+
+/// Seed of the treasury program address holding bids and the prize
+pub const TREASURY_SEED: &[u8] = b"treasury";
+
+/// Confirms the account is the program's treasury address, returns its bump seed
+pub fn check_treasury_account(program_id: &Pubkey, account: &AccountInfo) -> Result<u8, ProgramError> {
+    let (address, bump_seed) = Pubkey::find_program_address(&[TREASURY_SEED], program_id);
+    if address != *account.key {
+        msg!("Treasury account does not match the program address");
+        return Err(SixtyFourGameError::InvalidTreasuryAccount.into());
+    }
+    Ok(bump_seed)
+}
+
+/// Seed of the GameState program address
+pub const GAME_STATE_SEED: &[u8] = b"game_state";
+
+/// Confirms the account is the program's GameState address, returns its bump seed
+pub fn check_game_state_account(program_id: &Pubkey, account: &AccountInfo) -> Result<u8, ProgramError> {
+    let (address, bump_seed) = Pubkey::find_program_address(&[GAME_STATE_SEED], program_id);
+    if address != *account.key {
+        msg!("Game state account does not match the program address");
+        return Err(SixtyFourGameError::InvalidGameStateAccount.into());
+    }
+    Ok(bump_seed)
+}
+
+/// Seed of the auction list page program addresses, followed by the page number
+pub const AUCTION_LIST_SEED: &[u8] = b"auction_list";
+
+/// BidEntries stored in one auction list page
+pub const BIDS_PER_AUCTION_LIST_PAGE: u64 = 64;
+
+/// Auction list page holding the bid
+pub fn auction_list_page(bid_number: u64) -> u64 {
+    bid_number / BIDS_PER_AUCTION_LIST_PAGE
+}
+
+/// Offset of the bid's BidEntry inside its auction list page
+pub fn auction_list_offset(bid_number: u64) -> usize {
+    (bid_number % BIDS_PER_AUCTION_LIST_PAGE) as usize * BidEntry::LEN
+}
+
+/// Confirms the account is the program address of the auction list page, returns its bump seed
+pub fn check_auction_list_page_account(program_id: &Pubkey, page: u64, account: &AccountInfo) -> Result<u8, ProgramError> {
+    let (address, bump_seed) = Pubkey::find_program_address(&[AUCTION_LIST_SEED, &page.to_le_bytes()], program_id);
+    if address != *account.key {
+        msg!("Auction list account does not match the page program address");
+        return Err(SixtyFourGameError::InvalidAuctionListAccount.into());
+    }
+    Ok(bump_seed)
+}
+
+/// Confirms the account is the bid index saved in the AuctionConfig
+pub fn check_bid_index_account(auction_config: &AuctionConfig, account: &AccountInfo) -> ProgramResult {
+    if auction_config.bid_index_pubkey != *account.key {
+        msg!("Bid index account does not match the auction config");
+        return Err(SixtyFourGameError::InvalidBidIndexAccount.into());
+    }
+    Ok(())
+}
+
+/// Confirms the account is the program owned game squares list saved in the AuctionConfig
+pub fn check_all_game_squares_list_account(
+    program_id: &Pubkey,
+    auction_config: &AuctionConfig,
+    account: &AccountInfo,
+) -> ProgramResult {
+    if auction_config.all_game_squares_list_pubkey != *account.key || *account.owner != *program_id {
+        msg!("Game squares list account does not match the auction config");
+        return Err(SixtyFourGameError::InvalidGameSquaresListAccount.into());
+    }
+    Ok(())
+}
+
+/// Confirms the account is the program owned AuctionInfo saved in the AuctionConfig
+pub fn check_auction_info_account(
+    program_id: &Pubkey,
+    auction_config: &AuctionConfig,
+    account: &AccountInfo,
+) -> ProgramResult {
+    if auction_config.auction_info_pubkey != *account.key || *account.owner != *program_id {
+        msg!("Auction info account does not match the auction config");
+        return Err(SixtyFourGameError::InvalidAuctionInfoAccount.into());
+    }
+    Ok(())
+}
+
+/// Confirms the account is the program owned active players list saved in the AuctionConfig
+pub fn check_active_players_list_account(
+    program_id: &Pubkey,
+    auction_config: &AuctionConfig,
+    account: &AccountInfo,
+) -> ProgramResult {
+    if auction_config.active_players_list_pubkey != *account.key || *account.owner != *program_id {
+        msg!("Active players list account does not match the auction config");
+        return Err(SixtyFourGameError::InvalidActivePlayersListAccount.into());
+    }
+    Ok(())
+}
+
+/// Seed of the program token account escrowing bids and the prize when the auction has a bid
+/// mint, owned by the PROGRAM_TOKEN_OWNER_SEED program address
+pub const BID_ESCROW_SEED: &[u8] = b"bid_escrow";
+
+/// Confirms the account is the program's bid escrow address, returns its bump seed
+pub fn check_bid_escrow_account(program_id: &Pubkey, account: &AccountInfo) -> Result<u8, ProgramError> {
+    let (address, bump_seed) = Pubkey::find_program_address(&[BID_ESCROW_SEED], program_id);
+    if address != *account.key {
+        msg!("Bid escrow account does not match the program address");
+        return Err(SixtyFourGameError::InvalidBidTokenAccount.into());
+    }
+    Ok(bump_seed)
+}
+
+/// Creates an account owned by `owner` at a program address unless it already exists,
+/// signer_seeds end with the bump seed. Anyone can send lamports to the address beforehand,
+/// which makes create_account fail, so a funded address is topped up to rent exemption and
+/// allocated and assigned instead
+pub fn create_program_address_account<'a>(
+    payer: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    rent: &Rent,
+    space: usize,
+    signer_seeds: &[&[u8]],
+    owner: &Pubkey,
+) -> ProgramResult {
+    if *account.owner == *owner {
+        return Ok(());
+    }
+    let required_lamports = rent.minimum_balance(space);
+    if account.lamports() == 0 {
+        let create_instr = system_instruction::create_account(
+            payer.key,
+            account.key,
+            required_lamports,
+            space as u64,
+            owner,
+        );
+        return invoke_signed(
+            &create_instr,
+            &[
+                payer.clone(),
+                account.clone(),
+                system_program.clone(),
+            ],
+            &[signer_seeds],
+        );
+    }
+
+    let top_up_lamports = required_lamports.saturating_sub(account.lamports());
+    if top_up_lamports > 0 {
+        let transfer_instr = system_instruction::transfer(payer.key, account.key, top_up_lamports);
+        invoke(
+            &transfer_instr,
+            &[
+                payer.clone(),
+                account.clone(),
+                system_program.clone(),
+            ],
+        )?;
+    }
+    let allocate_instr = system_instruction::allocate(account.key, space as u64);
+    invoke_signed(
+        &allocate_instr,
+        &[account.clone(), system_program.clone()],
+        &[signer_seeds],
+    )?;
+    let assign_instr = system_instruction::assign(account.key, owner);
+    invoke_signed(
+        &assign_instr,
+        &[account.clone(), system_program.clone()],
+        &[signer_seeds],
+    )
 }
 
-const MAX_NUM_SLOT_HASHES: u64 = 512;
-pub fn get_slot_hash(data: &[u8], slot_height: u64) -> Hash {
-    let current_slot = u64::from_le_bytes(data[8..16].try_into().unwrap());
-    let diff = current_slot - slot_height;
-    if (diff > MAX_NUM_SLOT_HASHES) {
-        let buf = [0u8; HASH_BYTES];
-        return Hash::new(&buf);
-    } else {
-        let target_index = (16 + (diff * 40)) as usize;
-        let hash = &data[target_index..target_index + 32];
-        return Hash::new(hash);
+/// Confirms the account is a token account of the square's mint owned by `owner` and holding
+/// the square's NFT
+pub fn check_square_holder(account: &AccountInfo, mint: &Pubkey, owner: &Pubkey) -> ProgramResult {
+    if *account.owner != spl_token::id() {
+        msg!("Square token account is not a token account");
+        return Err(SixtyFourGameError::NotSquareOwner.into());
+    }
+    let token_info = TokenAccount::unpack(&account.data.borrow())?;
+    if token_info.mint != *mint || token_info.owner != *owner || token_info.amount != 1 {
+        msg!("Square token account does not hold the square's NFT");
+        return Err(SixtyFourGameError::NotSquareOwner.into());
     }
+    Ok(())
+}
+
+/// Seed of the program token accounts holding deposited square NFTs, with the square and mint
+pub const PROGRAM_TOKEN_ACCOUNT_SEED: &[u8] = b"square_token";
+
+/// Seed of the program address owning the program token accounts
+pub const PROGRAM_TOKEN_OWNER_SEED: &[u8] = b"initiate";
+
+/// Confirms the account is the program token account of the square and mint, returns its bump seed
+pub fn check_program_token_account(
+    program_id: &Pubkey,
+    square: u64,
+    mint: &Pubkey,
+    account: &AccountInfo,
+) -> Result<u8, ProgramError> {
+    let (address, bump_seed) = Pubkey::find_program_address(
+        &[PROGRAM_TOKEN_ACCOUNT_SEED, &square.to_le_bytes(), mint.as_ref()],
+        program_id,
+    );
+    if address != *account.key {
+        msg!("Program token account does not match the square's program address");
+        return Err(SixtyFourGameError::InvalidProgramTokenAccount.into());
+    }
+    Ok(bump_seed)
+}
+
+/// Confirms the account is the owner of the program token accounts, returns its bump seed
+pub fn check_program_token_owner(program_id: &Pubkey, account: &AccountInfo) -> Result<u8, ProgramError> {
+    let (address, bump_seed) = Pubkey::find_program_address(&[PROGRAM_TOKEN_OWNER_SEED], program_id);
+    if address != *account.key {
+        msg!("Program token owner does not match the program address");
+        return Err(SixtyFourGameError::InvalidProgramTokenAccount.into());
+    }
+    Ok(bump_seed)
 }
 
 pub fn unpack_mint(data: &[u8]) -> Result<Mint, SixtyFourGameError> {
     TokenPack::unpack(data).map_err(|_| SixtyFourGameError::ExpectedMint)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_runtime::{self, TestAccount};
+    use solana_sdk::system_program;
+
+    fn slot_hashes_data(entries: &[(u64, Hash)]) -> Vec<u8> {
+        let mut data = (entries.len() as u64).to_le_bytes().to_vec();
+        for (slot, hash) in entries {
+            data.extend_from_slice(&slot.to_le_bytes());
+            data.extend_from_slice(hash.as_ref());
+        }
+        data
+    }
+
+    #[test]
+    fn test_find_slot_hash() {
+        let entries = [
+            (105, Hash::new_from_array([5; 32])),
+            (104, Hash::new_from_array([4; 32])),
+            (101, Hash::new_from_array([1; 32])),
+            (100, Hash::new_from_array([0; 32])),
+        ];
+        let data = slot_hashes_data(&entries);
+        assert_eq!(find_slot_hash(&data, 104), Some(entries[1]));
+        // Skipped slots resolve to the next slot with a block
+        assert_eq!(find_slot_hash(&data, 102), Some(entries[1]));
+        assert_eq!(find_slot_hash(&data, 100), Some(entries[3]));
+        assert_eq!(find_slot_hash(&data, 106), None);
+        assert_eq!(find_slot_hash(&data, 99), None);
+        assert_eq!(find_slot_hash(&[], 100), None);
+    }
+
+    #[test]
+    fn test_attack_roll() {
+        let secret = [7; 32];
+        let slot_hash = Hash::new_from_array([9; 32]);
+        let roll = attack_roll(&secret, &slot_hash, 3, 4);
+        assert!(roll >= 1 && roll <= 100);
+        assert_eq!(roll, attack_roll(&secret, &slot_hash, 3, 4));
+        assert_eq!(attack_commitment(&secret), hashv(&[&secret]));
+        assert_ne!(attack_commitment(&secret), attack_commitment(&[8; 32]));
+    }
+
+    #[test]
+    fn test_bid_commitment() {
+        let salt = [7; 32];
+        let mut preimage = 500u64.to_le_bytes().to_vec();
+        preimage.extend_from_slice(&salt);
+        assert_eq!(bid_commitment(500, &salt), hashv(&[&preimage]));
+        assert_ne!(bid_commitment(500, &salt), bid_commitment(501, &salt));
+        assert_ne!(bid_commitment(500, &salt), bid_commitment(500, &[8; 32]));
+    }
+
+    #[test]
+    fn test_create_program_address_account_funded() {
+        let program_id = test_runtime::install();
+        let rent = Rent::default();
+        let required_lamports = rent.minimum_balance(TokenAccount::LEN);
+        let mut payer = TestAccount::new(Pubkey::new_unique(), 10_000_000_000, vec![], system_program::id());
+        let mut system = TestAccount::new(system_program::id(), 0, vec![], Pubkey::default());
+
+        // Program token account of a square funded past rent exemption, no top up needed
+        let mint = Pubkey::new_unique();
+        let seeds: &[&[u8]] = &[PROGRAM_TOKEN_ACCOUNT_SEED, &3u64.to_le_bytes(), mint.as_ref()];
+        let (address, bump_seed) = Pubkey::find_program_address(seeds, &program_id);
+        let mut token_account = TestAccount::new(address, required_lamports + 5, vec![0; TokenAccount::LEN], system_program::id());
+        create_program_address_account(
+            &payer.info(true),
+            &token_account.info(false),
+            &system.info(false),
+            &rent,
+            TokenAccount::LEN,
+            &[PROGRAM_TOKEN_ACCOUNT_SEED, &3u64.to_le_bytes(), mint.as_ref(), &[bump_seed]],
+            &spl_token::id(),
+        ).unwrap();
+        assert_eq!(token_account.owner(), spl_token::id());
+        assert_eq!(token_account.lamports, required_lamports + 5);
+        assert_eq!(payer.lamports, 10_000_000_000);
+
+        // Bid escrow funded below rent exemption is topped up by the payer
+        let (address, bump_seed) = Pubkey::find_program_address(&[BID_ESCROW_SEED], &program_id);
+        let mut bid_escrow = TestAccount::new(address, 1, vec![0; TokenAccount::LEN], system_program::id());
+        create_program_address_account(
+            &payer.info(true),
+            &bid_escrow.info(false),
+            &system.info(false),
+            &rent,
+            TokenAccount::LEN,
+            &[BID_ESCROW_SEED, &[bump_seed]],
+            &spl_token::id(),
+        ).unwrap();
+        assert_eq!(bid_escrow.owner(), spl_token::id());
+        assert_eq!(bid_escrow.lamports, required_lamports);
+        assert_eq!(payer.lamports, 10_000_000_000 - (required_lamports - 1));
+    }
+}